@@ -0,0 +1,86 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+#[cfg(feature = "async")]
+use esp_hal::Async;
+use esp_hal::{delay::Delay, gpio::OutputOpenDrain, Blocking, DriverMode};
+
+use crate::{FrameCodec, FrameInfo, Hc12Error, Hc12Framed, BROADCAST_ADDRESS};
+
+/// Node-to-node messaging over an [`Hc12Framed`] with addressing always enabled. Every frame
+/// carries the link header of the framed layer (destination, source, control, sequence), its
+/// length and integrity come from the codec. Frames addressed neither to the local address nor to
+/// [`BROADCAST_ADDRESS`] are dropped.
+pub struct AddressedHc12<
+    'd,
+    Dm: DriverMode,
+    C: FrameCodec,
+    const N: usize,
+    D = Delay,
+    S: OutputPin = OutputOpenDrain<'d>,
+> {
+    framed: Hc12Framed<'d, Dm, C, N, D, S>,
+}
+
+impl<'d, Dm: DriverMode, C: FrameCodec, const N: usize, D, S: OutputPin>
+    AddressedHc12<'d, Dm, C, N, D, S>
+{
+    pub fn new(mut framed: Hc12Framed<'d, Dm, C, N, D, S>, address: u8) -> Self {
+        framed.set_address(Some(address));
+
+        Self { framed }
+    }
+
+    pub fn address(&self) -> u8 {
+        self.framed.address().unwrap_or(BROADCAST_ADDRESS)
+    }
+
+    pub fn set_address(&mut self, address: u8) {
+        self.framed.set_address(Some(address));
+    }
+
+    pub fn inner(&mut self) -> &mut Hc12Framed<'d, Dm, C, N, D, S> {
+        &mut self.framed
+    }
+
+    pub fn into_inner(self) -> Hc12Framed<'d, Dm, C, N, D, S> {
+        self.framed
+    }
+}
+
+/// Sender and length of a received frame.
+fn sender(frame_info: FrameInfo) -> Result<(u8, usize), Hc12Error> {
+    let source = frame_info.source.ok_or(Hc12Error::Addressing)?;
+
+    Ok((source, frame_info.len))
+}
+
+impl<C: FrameCodec, const N: usize, D: DelayNs, S: OutputPin>
+    AddressedHc12<'_, Blocking, C, N, D, S>
+{
+    /// Sends `payload` to the node with address `destination`, or to all nodes with
+    /// [`BROADCAST_ADDRESS`].
+    pub fn send_to(&mut self, destination: u8, payload: &[u8]) -> Result<(), Hc12Error> {
+        self.framed.send_frame_to(destination, payload)
+    }
+
+    /// Blocks until a frame for this node arrives, copies its payload into `buffer` and returns
+    /// the sender's address and the payload length.
+    pub fn recv(&mut self, buffer: &mut [u8]) -> Result<(u8, usize), Hc12Error> {
+        sender(self.framed.recv_frame(buffer)?)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<C: FrameCodec, const N: usize, D, S: OutputPin> AddressedHc12<'_, Async, C, N, D, S> {
+    /// Sends `payload` to the node with address `destination`, or to all nodes with
+    /// [`BROADCAST_ADDRESS`].
+    pub async fn send_to(&mut self, destination: u8, payload: &[u8]) -> Result<(), Hc12Error> {
+        self.framed.send_frame_to(destination, payload).await
+    }
+
+    /// Waits until a frame for this node arrives, copies its payload into `buffer` and returns
+    /// the sender's address and the payload length.
+    pub async fn recv(&mut self, buffer: &mut [u8]) -> Result<(u8, usize), Hc12Error> {
+        sender(self.framed.recv_frame(buffer).await?)
+    }
+}
@@ -0,0 +1,206 @@
+use esp_hal::{
+    delay::Delay,
+    gpio::AnyPin,
+    peripheral::{Peripheral, PeripheralRef},
+    uart::{AnyUart, Config},
+    Blocking,
+};
+#[cfg(feature = "async")]
+use esp_hal::{interrupt::Priority, Async};
+
+use crate::hc_12::connect_uart;
+use crate::{
+    BaudRate, Channel, DutyCycle, Hc12, Hc12Config, Hc12Error, Hc12Timings, LineTerminator,
+    SetPolarity, TransmissionMode, TransmitPower, UartInversion, DEFAULT_MIN_RX_IDLE_MS,
+};
+
+/// Sets up an [`Hc12`] and its module in one go: the module settings, the wiring and the driver
+/// options are collected first, and [`Hc12Builder::build`] or [`Hc12Builder::build_async`] then
+/// runs the power-on sequence and applies everything with [`Hc12::bring_up`].
+///
+/// ```ignore
+/// let hc12 = Hc12Builder::new(peripherals.UART1, rx, tx, set)
+///     .baud(BaudRate::Baud115200)
+///     .channel(Channel::new_checked(5))
+///     .mode(TransmissionMode::Fu3)
+///     .power(PowerLevel::new_checked(4).into())
+///     .build_async()
+///     .await?;
+/// ```
+pub struct Hc12Builder<'d> {
+    uart: PeripheralRef<'d, AnyUart>,
+    rx: PeripheralRef<'d, AnyPin>,
+    tx: PeripheralRef<'d, AnyPin>,
+    set: PeripheralRef<'d, AnyPin>,
+    config: Hc12Config,
+    uart_config: Config,
+    set_polarity: SetPolarity,
+    inversion: UartInversion,
+    line_terminator: LineTerminator,
+    duty_cycle: Option<DutyCycle>,
+    min_rx_idle_ms: u32,
+    timings: Hc12Timings,
+    #[cfg(feature = "async")]
+    interrupt_priority: Option<Priority>,
+}
+
+impl<'d> Hc12Builder<'d> {
+    /// Starts from the factory settings, `Config::default()` for the UART and the defaults of
+    /// [`Hc12::new`].
+    pub fn new(
+        uart: impl Peripheral<P = AnyUart> + 'd,
+        rx: impl Peripheral<P = AnyPin> + 'd,
+        tx: impl Peripheral<P = AnyPin> + 'd,
+        set: impl Peripheral<P = AnyPin> + 'd,
+    ) -> Self {
+        Self {
+            uart: uart.into_ref(),
+            rx: rx.into_ref(),
+            tx: tx.into_ref(),
+            set: set.into_ref(),
+            config: Hc12Config::default(),
+            uart_config: Config::default(),
+            set_polarity: SetPolarity::default(),
+            inversion: UartInversion::default(),
+            line_terminator: LineTerminator::default(),
+            duty_cycle: None,
+            min_rx_idle_ms: DEFAULT_MIN_RX_IDLE_MS,
+            timings: Hc12Timings::default(),
+            #[cfg(feature = "async")]
+            interrupt_priority: None,
+        }
+    }
+
+    pub fn baud(mut self, baud_rate: BaudRate) -> Self {
+        self.config.baud_rate = baud_rate;
+        self
+    }
+
+    pub fn channel(mut self, channel: Channel) -> Self {
+        self.config.channel = channel;
+        self
+    }
+
+    pub fn mode(mut self, transmission_mode: TransmissionMode) -> Self {
+        self.config.transmission_mode = transmission_mode;
+        self
+    }
+
+    pub fn power(mut self, transmit_power: TransmitPower) -> Self {
+        self.config.transmit_power = transmit_power;
+        self
+    }
+
+    /// Replaces all module settings at once.
+    pub fn config(mut self, config: Hc12Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// UART settings other than the baud rate, e.g. the RX FIFO threshold and RX timeout. The baud
+    /// rate only matters for the first contact, `bring_up` finds the module at any rate.
+    pub fn uart_config(mut self, uart_config: Config) -> Self {
+        self.uart_config = uart_config;
+        self
+    }
+
+    pub fn set_polarity(mut self, set_polarity: SetPolarity) -> Self {
+        self.set_polarity = set_polarity;
+        self
+    }
+
+    pub fn inversion(mut self, inversion: UartInversion) -> Self {
+        self.inversion = inversion;
+        self
+    }
+
+    pub fn line_terminator(mut self, line_terminator: LineTerminator) -> Self {
+        self.line_terminator = line_terminator;
+        self
+    }
+
+    pub fn duty_cycle(mut self, duty_cycle: DutyCycle) -> Self {
+        self.duty_cycle = Some(duty_cycle);
+        self
+    }
+
+    pub fn min_rx_idle_ms(mut self, min_rx_idle_ms: u32) -> Self {
+        self.min_rx_idle_ms = min_rx_idle_ms;
+        self
+    }
+
+    /// Applied before the module is configured, so `bring_up` already runs with them.
+    pub fn timings(mut self, timings: Hc12Timings) -> Self {
+        self.timings = timings;
+        self
+    }
+
+    /// Priority of the UART's interrupt for [`Hc12Builder::build_async`]; see
+    /// [`Hc12::set_interrupt_priority`].
+    #[cfg(feature = "async")]
+    pub fn interrupt_priority(mut self, priority: Priority) -> Self {
+        self.interrupt_priority = Some(priority);
+        self
+    }
+
+    /// Builds a blocking driver. Fails with [`Hc12Error::IncompatibleMode`] before touching the
+    /// module if the settings don't go together, e.g. FU4 at another baud rate than 1200.
+    pub fn build(self) -> Result<Hc12<'d, Blocking>, Hc12Error> {
+        self.config.validate()?;
+
+        let (uart, wiring) = connect_uart(
+            self.uart,
+            self.rx,
+            self.tx,
+            self.uart_config,
+            self.inversion,
+        )?;
+        let mut hc12 = Hc12::<Blocking>::init(
+            uart,
+            Some(wiring),
+            self.set,
+            self.uart_config,
+            Delay::new(),
+            self.set_polarity,
+        )?;
+        hc12.set_line_terminator(self.line_terminator);
+        hc12.set_duty_cycle(self.duty_cycle);
+        hc12.set_min_rx_idle_ms(self.min_rx_idle_ms);
+        hc12.set_timings(self.timings);
+        hc12.bring_up(&self.config)?;
+
+        Ok(hc12)
+    }
+
+    /// Async version of [`Hc12Builder::build`].
+    #[cfg(feature = "async")]
+    pub async fn build_async(self) -> Result<Hc12<'d, Async>, Hc12Error> {
+        self.config.validate()?;
+
+        let (uart, wiring) = connect_uart(
+            self.uart,
+            self.rx,
+            self.tx,
+            self.uart_config,
+            self.inversion,
+        )?;
+        let mut hc12 = Hc12::<Async>::init(
+            uart,
+            Some(wiring),
+            self.set,
+            self.uart_config,
+            self.set_polarity,
+        )
+        .await?;
+        if let Some(priority) = self.interrupt_priority {
+            hc12.set_interrupt_priority(priority)?;
+        }
+        hc12.set_line_terminator(self.line_terminator);
+        hc12.set_duty_cycle(self.duty_cycle);
+        hc12.set_min_rx_idle_ms(self.min_rx_idle_ms);
+        hc12.set_timings(self.timings);
+        hc12.bring_up(&self.config).await?;
+
+        Ok(hc12)
+    }
+}
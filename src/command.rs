@@ -0,0 +1,516 @@
+use heapless::Vec;
+
+use crate::protocol::{self, COMMAND_LEN};
+use crate::{
+    AutoBaudFailure, BaudRate, Channel, Hc12Error, Hc12Params, TransmissionMode, TransmitPower,
+};
+
+/// Longest response the module sends to any single setting command.
+pub(crate) const RESPONSE_LEN: usize = COMMAND_LEN;
+
+/// Room for the four lines the module answers `AT+RX` with.
+pub(crate) const PARAMS_RESPONSE_LEN: usize = 64;
+
+/// Pause after which a command response is considered complete when no further byte arrived; two
+/// characters at 1200 baud.
+pub(crate) const RESPONSE_IDLE_MS: u32 = 20;
+
+/// Sends AT commands to a module and collects its responses.
+pub trait CommandTransport {
+    /// Sends `command` in command mode and reads the module's response into `response`, returning
+    /// the number of bytes received.
+    fn exchange(&mut self, command: &[u8], response: &mut [u8]) -> Result<usize, Hc12Error>;
+}
+
+/// A [`CommandTransport`] whose host side can follow the module to another baud rate.
+pub trait BaudSwitch: CommandTransport {
+    /// Sends what is still queued at the current baud rate, then moves the host side to
+    /// `baud_rate`.
+    fn switch_host_baud(&mut self, baud_rate: BaudRate) -> Result<(), Hc12Error>;
+
+    /// Waits `ms` milliseconds.
+    fn wait_ms(&mut self, ms: u32);
+}
+
+/// The baud rates `auto_baud` probes, in order.
+pub(crate) const PROBE_BAUD_RATES: [BaudRate; 8] = [
+    BaudRate::Baud1200,
+    BaudRate::Baud2400,
+    BaudRate::Baud4800,
+    BaudRate::Baud9600,
+    BaudRate::Baud19200,
+    BaudRate::Baud38400,
+    BaudRate::Baud57600,
+    BaudRate::Baud115200,
+];
+
+/// Sends `command` and returns the raw response.
+fn send<T: CommandTransport>(
+    transport: &mut T,
+    command: &str,
+) -> Result<Vec<u8, RESPONSE_LEN>, Hc12Error> {
+    let mut buffer = [0u8; RESPONSE_LEN];
+    let bytes_read = transport.exchange(command.as_bytes(), &mut buffer)?;
+
+    Ok(Vec::from_slice(&buffer[..bytes_read]).unwrap())
+}
+
+pub(crate) fn test<T: CommandTransport>(transport: &mut T) -> Result<(), Hc12Error> {
+    protocol::parse_test_response(&send(transport, protocol::TEST_COMMAND)?)
+}
+
+/// Finds the module's baud rate by probing every rate with `AT`, leaving the host side at the
+/// one that answered. Fails with [`Hc12Error::AutoBaudRate`] and what the probes saw instead.
+pub(crate) fn auto_baud<T: BaudSwitch>(transport: &mut T) -> Result<BaudRate, Hc12Error> {
+    let mut failure = AutoBaudFailure::NoResponseAtAnyBaud;
+    for baud_rate in PROBE_BAUD_RATES {
+        transport.switch_host_baud(baud_rate)?;
+        transport.wait_ms(baud_rate.probe_settle_ms());
+
+        let mut buffer = [0u8; RESPONSE_LEN];
+        let result = transport.exchange(protocol::TEST_COMMAND.as_bytes(), &mut buffer);
+        let response = match result {
+            Ok(bytes_read) => &buffer[..bytes_read],
+            Err(_) => &[],
+        };
+        match result.and_then(|_| protocol::parse_test_response(response)) {
+            Ok(()) => return Ok(baud_rate),
+            Err(error) => {
+                failure = failure.merge(AutoBaudFailure::classify(baud_rate, &error, response))
+            }
+        }
+    }
+
+    failure.log();
+    Err(Hc12Error::AutoBaudRate(failure))
+}
+
+pub(crate) fn set_baud<T: CommandTransport>(
+    transport: &mut T,
+    baud_rate: &BaudRate,
+) -> Result<(), Hc12Error> {
+    let response = send(transport, &protocol::format_set_baud(baud_rate)?)?;
+
+    if protocol::parse_baud_response(&response)? != *baud_rate {
+        return Err(Hc12Error::BaudRate);
+    }
+
+    Ok(())
+}
+
+/// Changes baud rate and channel through a `transport` that keeps the module in command mode
+/// between the commands, so both go out at the current baud rate. If the channel is rejected the
+/// baud rate is set back to `previous_baud_rate`; the error then carries whether that worked.
+pub(crate) fn set_baud_and_channel<T: CommandTransport>(
+    transport: &mut T,
+    baud_rate: &BaudRate,
+    channel: Channel,
+    previous_baud_rate: &BaudRate,
+) -> Result<(), (Hc12Error, bool)> {
+    set_baud(transport, baud_rate).map_err(|error| (error, true))?;
+    set_channel(transport, channel).map_err(|error| {
+        let reverted = set_baud(transport, previous_baud_rate).is_ok();
+        (error, reverted)
+    })
+}
+
+/// Switches the transmission mode and returns the baud rate the module moved to, if the mode
+/// change forced one.
+pub(crate) fn set_transmission_mode<T: CommandTransport>(
+    transport: &mut T,
+    transmission_mode: &TransmissionMode,
+) -> Result<Result<Option<BaudRate>, Hc12Error>, Hc12Error> {
+    let response = send(
+        transport,
+        &protocol::format_set_transmission_mode(transmission_mode)?,
+    )?;
+
+    confirm_transmission_mode(&response, transmission_mode)
+}
+
+/// Checks that `response` confirms `transmission_mode`, shared by the blocking and async drivers
+/// so both accept the same responses, with or without a forced baud rate and line ending.
+pub(crate) fn confirm_transmission_mode(
+    response: &[u8],
+    transmission_mode: &TransmissionMode,
+) -> Result<Result<Option<BaudRate>, Hc12Error>, Hc12Error> {
+    let (confirmed_mode, baud_rate) = protocol::parse_transmission_mode_response(response)?;
+    if confirmed_mode != *transmission_mode {
+        return Err(Hc12Error::TransmissionMode);
+    }
+
+    Ok(baud_rate)
+}
+
+pub(crate) fn set_channel<T: CommandTransport>(
+    transport: &mut T,
+    channel: Channel,
+) -> Result<(), Hc12Error> {
+    let response = send(transport, &protocol::format_set_channel(channel)?)?;
+
+    if protocol::parse_channel_response(&response)? != channel {
+        return Err(Hc12Error::Channel);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn set_transmit_power<T: CommandTransport>(
+    transport: &mut T,
+    transmit_power: &TransmitPower,
+) -> Result<(), Hc12Error> {
+    let response = send(
+        transport,
+        &protocol::format_set_transmit_power(transmit_power)?,
+    )?;
+
+    if protocol::parse_transmit_power_response(&response)? != *transmit_power {
+        return Err(Hc12Error::TransmitPower);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn set_default<T: CommandTransport>(transport: &mut T) -> Result<(), Hc12Error> {
+    let response = send(transport, protocol::DEFAULT_COMMAND).map_err(|_| Hc12Error::Default)?;
+
+    protocol::parse_default_response(&response)
+}
+
+#[cfg(any(test, feature = "esp32c3"))]
+pub(crate) fn sleep<T: CommandTransport>(transport: &mut T) -> Result<(), Hc12Error> {
+    protocol::parse_sleep_response(&send(transport, protocol::SLEEP_COMMAND)?)
+}
+
+pub(crate) fn get_channel<T: CommandTransport>(transport: &mut T) -> Result<Channel, Hc12Error> {
+    protocol::parse_get_channel_response(&send(transport, protocol::GET_CHANNEL_COMMAND)?)
+}
+
+pub(crate) fn get_params<T: CommandTransport>(transport: &mut T) -> Result<Hc12Params, Hc12Error> {
+    let mut buffer = [0u8; PARAMS_RESPONSE_LEN];
+    let bytes_read = transport.exchange(protocol::GET_PARAMS_COMMAND.as_bytes(), &mut buffer)?;
+
+    protocol::parse_params_response(&buffer[..bytes_read])
+}
+
+/// Longest command recorded by [`MockTransport`].
+#[cfg(any(test, feature = "mock"))]
+pub const MOCK_COMMAND_LEN: usize = 16;
+
+/// [`CommandTransport`] answering each command with the next of a list of canned responses, for
+/// exercising the command layer without a module. Commands without a canned response receive an
+/// empty response, like a module that doesn't answer.
+///
+/// [`MockTransport::sent_exactly`] pins down the wire format, so a change to the padding, the
+/// line ending or a command letter shows up as a mismatch instead of a module that stops
+/// answering.
+#[cfg(any(test, feature = "mock"))]
+pub struct MockTransport<'a, const N: usize> {
+    responses: &'a [&'a [u8]],
+    next_response: usize,
+    commands: Vec<Vec<u8, MOCK_COMMAND_LEN>, N>,
+    overflowed: bool,
+}
+
+#[cfg(any(test, feature = "mock"))]
+impl<'a, const N: usize> MockTransport<'a, N> {
+    pub fn new(responses: &'a [&'a [u8]]) -> Self {
+        Self {
+            responses,
+            next_response: 0,
+            commands: Vec::new(),
+            overflowed: false,
+        }
+    }
+
+    /// The first `N` commands received, each truncated to [`MOCK_COMMAND_LEN`] bytes.
+    pub fn commands(&self) -> impl Iterator<Item = &[u8]> {
+        self.commands.iter().map(|command| command.as_slice())
+    }
+
+    /// Whether exactly the `expected` commands were sent, byte for byte and in order. Fails if a
+    /// command was truncated or dropped for lack of room, so a match is never partial.
+    pub fn sent_exactly(&self, expected: &[&[u8]]) -> bool {
+        !self.overflowed && self.commands().eq(expected.iter().copied())
+    }
+
+    /// Number of canned responses handed out so far.
+    pub fn responses_used(&self) -> usize {
+        self.next_response
+    }
+}
+
+#[cfg(any(test, feature = "mock"))]
+impl<const N: usize> CommandTransport for MockTransport<'_, N> {
+    fn exchange(&mut self, command: &[u8], response: &mut [u8]) -> Result<usize, Hc12Error> {
+        self.overflowed |= command.len() > MOCK_COMMAND_LEN || self.commands.is_full();
+        let command = &command[..command.len().min(MOCK_COMMAND_LEN)];
+        let _ = self.commands.push(Vec::from_slice(command).unwrap());
+
+        let Some(canned) = self.responses.get(self.next_response) else {
+            return Ok(0);
+        };
+        self.next_response += 1;
+
+        let len = canned.len().min(response.len());
+        response[..len].copy_from_slice(&canned[..len]);
+
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_transmission_mode_accepts_confirmation() {
+        let mut transport = MockTransport::<1>::new(&[b"OK+FU3\r\n"]);
+
+        assert_eq!(
+            set_transmission_mode(&mut transport, &TransmissionMode::Fu3),
+            Ok(Ok(None))
+        );
+    }
+
+    #[test]
+    fn set_transmission_mode_returns_forced_baud_rate() {
+        let mut transport = MockTransport::<1>::new(&[b"OK+FU4,B1200\r\n"]);
+
+        assert_eq!(
+            set_transmission_mode(&mut transport, &TransmissionMode::Fu4),
+            Ok(Ok(Some(BaudRate::Baud1200)))
+        );
+    }
+
+    #[test]
+    fn set_transmission_mode_rejects_other_mode() {
+        let mut transport = MockTransport::<1>::new(&[b"OK+FU2\r\n"]);
+
+        assert_eq!(
+            set_transmission_mode(&mut transport, &TransmissionMode::Fu3),
+            Err(Hc12Error::TransmissionMode)
+        );
+    }
+
+    #[test]
+    fn set_transmission_mode_reports_error_response() {
+        let mut transport = MockTransport::<1>::new(&[b"ERROR\r\n"]);
+
+        assert!(matches!(
+            set_transmission_mode(&mut transport, &TransmissionMode::Fu3),
+            Err(Hc12Error::ModuleRejected(_))
+        ));
+    }
+
+    #[test]
+    fn set_transmission_mode_rejects_garbage() {
+        let mut transport = MockTransport::<1>::new(&[b"\xff\x00+F"]);
+
+        assert_eq!(
+            set_transmission_mode(&mut transport, &TransmissionMode::Fu3),
+            Err(Hc12Error::TransmissionMode)
+        );
+    }
+
+    #[test]
+    fn confirm_transmission_mode_with_forced_baud_rate() {
+        assert_eq!(
+            confirm_transmission_mode(b"OK+FU4,B1200\r\n", &TransmissionMode::Fu4),
+            Ok(Ok(Some(BaudRate::Baud1200)))
+        );
+    }
+
+    #[test]
+    fn confirm_transmission_mode_without_baud_rate() {
+        assert_eq!(
+            confirm_transmission_mode(b"OK+FU3\r\n", &TransmissionMode::Fu3),
+            Ok(Ok(None))
+        );
+    }
+
+    #[test]
+    fn confirm_transmission_mode_without_line_ending() {
+        assert_eq!(
+            confirm_transmission_mode(b"OK+FU3", &TransmissionMode::Fu3),
+            Ok(Ok(None))
+        );
+    }
+
+    #[test]
+    fn confirm_transmission_mode_keeps_mode_on_unparsable_baud_rate() {
+        assert_eq!(
+            confirm_transmission_mode(b"OK+FU4,X1200\r\n", &TransmissionMode::Fu4),
+            Ok(Err(Hc12Error::InvalidResponse))
+        );
+    }
+
+    #[test]
+    fn confirm_transmission_mode_reports_error_response() {
+        assert!(matches!(
+            confirm_transmission_mode(b"ERROR\r\n", &TransmissionMode::Fu1),
+            Err(Hc12Error::ModuleRejected(_))
+        ));
+    }
+
+    #[test]
+    fn confirm_transmission_mode_rejects_garbage() {
+        assert_eq!(
+            confirm_transmission_mode(b"OK+FUX", &TransmissionMode::Fu1),
+            Err(Hc12Error::TransmissionMode)
+        );
+    }
+
+    #[test]
+    fn set_baud_accepts_confirmation() {
+        let mut transport = MockTransport::<1>::new(&[b"OK+B19200\r\n"]);
+
+        assert_eq!(set_baud(&mut transport, &BaudRate::Baud19200), Ok(()));
+    }
+
+    #[test]
+    fn set_baud_rejects_other_baud_rate() {
+        let mut transport = MockTransport::<1>::new(&[b"OK+B9600\r\n"]);
+
+        assert_eq!(
+            set_baud(&mut transport, &BaudRate::Baud19200),
+            Err(Hc12Error::BaudRate)
+        );
+    }
+
+    #[test]
+    fn set_baud_reports_error_response() {
+        let mut transport = MockTransport::<1>::new(&[b"ERROR\r\n"]);
+
+        assert!(matches!(
+            set_baud(&mut transport, &BaudRate::Baud19200),
+            Err(Hc12Error::ModuleRejected(_))
+        ));
+    }
+
+    #[test]
+    fn set_baud_rejects_garbage() {
+        let mut transport = MockTransport::<1>::new(&[b"\x80OK+B"]);
+
+        assert_eq!(
+            set_baud(&mut transport, &BaudRate::Baud19200),
+            Err(Hc12Error::BaudRate)
+        );
+    }
+
+    #[test]
+    fn set_baud_fails_without_response() {
+        let mut transport = MockTransport::<1>::new(&[]);
+
+        assert_eq!(
+            set_baud(&mut transport, &BaudRate::Baud19200),
+            Err(Hc12Error::BaudRate)
+        );
+    }
+
+    #[test]
+    fn sends_test_command() {
+        let mut transport = MockTransport::<1>::new(&[b"OK\r\n"]);
+
+        assert_eq!(test(&mut transport), Ok(()));
+        assert!(transport.sent_exactly(&[b"AT"]));
+    }
+
+    #[test]
+    fn sends_set_baud_command() {
+        let mut transport = MockTransport::<2>::new(&[b"OK+B9600\r\n", b"OK+B115200\r\n"]);
+
+        assert_eq!(set_baud(&mut transport, &BaudRate::Baud9600), Ok(()));
+        assert_eq!(set_baud(&mut transport, &BaudRate::Baud115200), Ok(()));
+        assert!(transport.sent_exactly(&[b"AT+B9600", b"AT+B115200"]));
+    }
+
+    #[test]
+    fn sends_zero_padded_channel() {
+        let mut transport =
+            MockTransport::<3>::new(&[b"OK+C001\r\n", b"OK+C042\r\n", b"OK+C127\r\n"]);
+
+        for channel in [1, 42, 127] {
+            assert_eq!(
+                set_channel(&mut transport, Channel::new(channel).unwrap()),
+                Ok(())
+            );
+        }
+        assert!(transport.sent_exactly(&[b"AT+C001", b"AT+C042", b"AT+C127"]));
+    }
+
+    #[test]
+    fn sends_set_transmit_power_command() {
+        let mut transport = MockTransport::<2>::new(&[b"OK+P1\r\n", b"OK+P8\r\n"]);
+
+        assert_eq!(
+            set_transmit_power(&mut transport, &TransmitPower::P1),
+            Ok(())
+        );
+        assert_eq!(
+            set_transmit_power(&mut transport, &TransmitPower::P8),
+            Ok(())
+        );
+        assert!(transport.sent_exactly(&[b"AT+P1", b"AT+P8"]));
+    }
+
+    #[test]
+    fn sends_set_transmission_mode_command() {
+        let mut transport = MockTransport::<2>::new(&[b"OK+FU1\r\n", b"OK+FU4,B1200\r\n"]);
+
+        assert!(set_transmission_mode(&mut transport, &TransmissionMode::Fu1).is_ok());
+        assert!(set_transmission_mode(&mut transport, &TransmissionMode::Fu4).is_ok());
+        assert!(transport.sent_exactly(&[b"AT+FU1", b"AT+FU4"]));
+    }
+
+    #[test]
+    fn sends_default_command() {
+        let mut transport = MockTransport::<1>::new(&[b"OK+DEFAULT\r\n"]);
+
+        assert_eq!(set_default(&mut transport), Ok(()));
+        assert!(transport.sent_exactly(&[b"AT+DEFAULT"]));
+    }
+
+    #[test]
+    fn sends_sleep_command() {
+        let mut transport = MockTransport::<1>::new(&[b"OK+SLEEP\r\n"]);
+
+        assert_eq!(sleep(&mut transport), Ok(()));
+        assert!(transport.sent_exactly(&[b"AT+SLEEP"]));
+    }
+
+    #[test]
+    fn sends_get_channel_command() {
+        let mut transport = MockTransport::<1>::new(&[b"OK+RC042\r\n"]);
+
+        assert_eq!(get_channel(&mut transport), Channel::try_from(42));
+        assert!(transport.sent_exactly(&[b"AT+RC"]));
+    }
+
+    #[test]
+    fn sends_get_params_command() {
+        let mut transport =
+            MockTransport::<1>::new(&[b"OK+B9600\r\nOK+RC042\r\nOK+RP:+20dBm\r\nOK+FU3\r\n"]);
+
+        assert_eq!(
+            get_params(&mut transport),
+            Ok(Hc12Params {
+                baud_rate: BaudRate::Baud9600,
+                channel: Channel::new(42).unwrap(),
+                transmission_mode: TransmissionMode::Fu3,
+                transmit_power: TransmitPower::P8,
+            })
+        );
+        assert!(transport.sent_exactly(&[b"AT+RX"]));
+    }
+
+    #[test]
+    fn sent_exactly_fails_on_dropped_commands() {
+        let mut transport = MockTransport::<1>::new(&[b"OK\r\n", b"OK\r\n"]);
+
+        assert_eq!(test(&mut transport), Ok(()));
+        assert_eq!(test(&mut transport), Ok(()));
+        assert!(!transport.sent_exactly(&[b"AT"]));
+        assert_eq!(transport.responses_used(), 2);
+    }
+}
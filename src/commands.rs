@@ -0,0 +1,229 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+#[cfg(feature = "async")]
+use esp_hal::Async;
+use esp_hal::Blocking;
+
+use crate::{
+    BaudRate, Channel, Hc12, Hc12Config, Hc12Error, Hc12Params, TransmissionMode, TransmitPower,
+};
+
+/// Command and I/O methods of the blocking driver, with the same names, arguments and error type
+/// as [`Hc12CommandsAsync`] for the async driver. Code written against one trait ports to the other
+/// by adding `.await`:
+///
+/// ```no_run
+/// # use esp_hal::Blocking;
+/// # use hc_12::{Channel, Hc12, Hc12Commands, Hc12Error};
+/// fn configure<H: Hc12Commands>(hc12: &mut H) -> Result<(), Hc12Error> {
+///     hc12.set_channel(Channel::new_checked(42))?;
+///     hc12.write_all(b"configured")?;
+///     hc12.flush()
+/// }
+///
+/// # #[cfg(feature = "async")]
+/// # use {esp_hal::Async, hc_12::Hc12CommandsAsync};
+/// # #[cfg(feature = "async")]
+/// async fn configure_async<H: Hc12CommandsAsync>(hc12: &mut H) -> Result<(), Hc12Error> {
+///     hc12.set_channel(Channel::new_checked(42)).await?;
+///     hc12.write_all(b"configured").await?;
+///     hc12.flush().await
+/// }
+///
+/// fn start(hc12: &mut Hc12<'_, Blocking>) -> Result<(), Hc12Error> {
+///     configure(hc12)
+/// }
+///
+/// # #[cfg(feature = "async")]
+/// async fn start_async(hc12: &mut Hc12<'_, Async>) -> Result<(), Hc12Error> {
+///     configure_async(hc12).await
+/// }
+/// # fn main() {}
+/// ```
+pub trait Hc12Commands {
+    fn test(&mut self) -> Result<(), Hc12Error>;
+
+    fn auto_baud(&mut self) -> Result<BaudRate, Hc12Error>;
+
+    fn set_baud(&mut self, baud_rate: &BaudRate) -> Result<(), Hc12Error>;
+
+    fn set_transmission_mode(
+        &mut self,
+        transmission_mode: &TransmissionMode,
+    ) -> Result<(), Hc12Error>;
+
+    fn set_channel(&mut self, channel: Channel) -> Result<(), Hc12Error>;
+
+    fn set_transmit_power(&mut self, transmit_power: &TransmitPower) -> Result<(), Hc12Error>;
+
+    fn set_default(&mut self) -> Result<(), Hc12Error>;
+
+    fn get_channel(&mut self) -> Result<Channel, Hc12Error>;
+
+    fn get_params(&mut self) -> Result<Hc12Params, Hc12Error>;
+
+    fn bring_up(&mut self, target: &Hc12Config) -> Result<BaudRate, Hc12Error>;
+
+    fn write_all(&mut self, data: &[u8]) -> Result<(), Hc12Error>;
+
+    fn flush(&mut self) -> Result<(), Hc12Error>;
+
+    /// Waits until at least one byte has been received and reads as many as are available and fit
+    /// into `buffer`.
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Hc12Error>;
+}
+
+/// Async counterpart of [`Hc12Commands`].
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait Hc12CommandsAsync {
+    async fn test(&mut self) -> Result<(), Hc12Error>;
+
+    async fn auto_baud(&mut self) -> Result<BaudRate, Hc12Error>;
+
+    async fn set_baud(&mut self, baud_rate: &BaudRate) -> Result<(), Hc12Error>;
+
+    async fn set_transmission_mode(
+        &mut self,
+        transmission_mode: &TransmissionMode,
+    ) -> Result<(), Hc12Error>;
+
+    async fn set_channel(&mut self, channel: Channel) -> Result<(), Hc12Error>;
+
+    async fn set_transmit_power(&mut self, transmit_power: &TransmitPower)
+        -> Result<(), Hc12Error>;
+
+    async fn set_default(&mut self) -> Result<(), Hc12Error>;
+
+    async fn get_channel(&mut self) -> Result<Channel, Hc12Error>;
+
+    async fn get_params(&mut self) -> Result<Hc12Params, Hc12Error>;
+
+    async fn bring_up(&mut self, target: &Hc12Config) -> Result<BaudRate, Hc12Error>;
+
+    async fn write_all(&mut self, data: &[u8]) -> Result<(), Hc12Error>;
+
+    async fn flush(&mut self) -> Result<(), Hc12Error>;
+
+    /// Waits until at least one byte has been received and reads as many as are available and fit
+    /// into `buffer`.
+    async fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Hc12Error>;
+}
+
+impl<D: DelayNs, S: OutputPin> Hc12Commands for Hc12<'_, Blocking, D, S> {
+    fn test(&mut self) -> Result<(), Hc12Error> {
+        Self::test(self)
+    }
+
+    fn auto_baud(&mut self) -> Result<BaudRate, Hc12Error> {
+        Self::auto_baud(self)
+    }
+
+    fn set_baud(&mut self, baud_rate: &BaudRate) -> Result<(), Hc12Error> {
+        Self::set_baud(self, baud_rate)
+    }
+
+    fn set_transmission_mode(
+        &mut self,
+        transmission_mode: &TransmissionMode,
+    ) -> Result<(), Hc12Error> {
+        Self::set_transmission_mode(self, transmission_mode)
+    }
+
+    fn set_channel(&mut self, channel: Channel) -> Result<(), Hc12Error> {
+        Self::set_channel(self, channel)
+    }
+
+    fn set_transmit_power(&mut self, transmit_power: &TransmitPower) -> Result<(), Hc12Error> {
+        Self::set_transmit_power(self, transmit_power)
+    }
+
+    fn set_default(&mut self) -> Result<(), Hc12Error> {
+        Self::set_default(self)
+    }
+
+    fn get_channel(&mut self) -> Result<Channel, Hc12Error> {
+        Self::get_channel(self)
+    }
+
+    fn get_params(&mut self) -> Result<Hc12Params, Hc12Error> {
+        Self::get_params(self)
+    }
+
+    fn bring_up(&mut self, target: &Hc12Config) -> Result<BaudRate, Hc12Error> {
+        Self::bring_up(self, target)
+    }
+
+    fn write_all(&mut self, data: &[u8]) -> Result<(), Hc12Error> {
+        Self::write_all(self, data)
+    }
+
+    fn flush(&mut self) -> Result<(), Hc12Error> {
+        Self::flush(self)
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Hc12Error> {
+        Ok(self.read_some(buffer)?)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<D, S: OutputPin> Hc12CommandsAsync for Hc12<'_, Async, D, S> {
+    async fn test(&mut self) -> Result<(), Hc12Error> {
+        Self::test(self).await
+    }
+
+    async fn auto_baud(&mut self) -> Result<BaudRate, Hc12Error> {
+        Self::auto_baud(self).await
+    }
+
+    async fn set_baud(&mut self, baud_rate: &BaudRate) -> Result<(), Hc12Error> {
+        Self::set_baud(self, baud_rate).await
+    }
+
+    async fn set_transmission_mode(
+        &mut self,
+        transmission_mode: &TransmissionMode,
+    ) -> Result<(), Hc12Error> {
+        Self::set_transmission_mode(self, transmission_mode).await
+    }
+
+    async fn set_channel(&mut self, channel: Channel) -> Result<(), Hc12Error> {
+        Self::set_channel(self, channel).await
+    }
+
+    async fn set_transmit_power(
+        &mut self,
+        transmit_power: &TransmitPower,
+    ) -> Result<(), Hc12Error> {
+        Self::set_transmit_power(self, transmit_power).await
+    }
+
+    async fn set_default(&mut self) -> Result<(), Hc12Error> {
+        Self::set_default(self).await
+    }
+
+    async fn get_channel(&mut self) -> Result<Channel, Hc12Error> {
+        Self::get_channel(self).await
+    }
+
+    async fn get_params(&mut self) -> Result<Hc12Params, Hc12Error> {
+        Self::get_params(self).await
+    }
+
+    async fn bring_up(&mut self, target: &Hc12Config) -> Result<BaudRate, Hc12Error> {
+        Self::bring_up(self, target).await
+    }
+
+    async fn write_all(&mut self, data: &[u8]) -> Result<(), Hc12Error> {
+        self.write_all_async(data).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Hc12Error> {
+        Ok(self.flush_async().await?)
+    }
+
+    async fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Hc12Error> {
+        Ok(self.read_async(buffer).await?)
+    }
+}
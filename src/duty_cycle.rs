@@ -0,0 +1,75 @@
+/// Limits the share of time the module transmits to `percent` of a `window_ms` long window, as
+/// required by duty-cycle regulations (e.g. 10 % per hour in parts of the 433 MHz band).
+///
+/// The budget is a bucket holding up to one window's worth of air time that refills continuously
+/// at the allowed rate. Every transmission is charged with its estimated air time at the current
+/// mode and baud rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DutyCycle {
+    percent: u8,
+    window_ms: u32,
+    // Air time budget in hundredths of a millisecond, so that slow refills aren't rounded away.
+    available: u64,
+    last_refill_ms: Option<u64>,
+}
+
+impl DutyCycle {
+    /// Creates a governor with a full budget. `percent` is clamped to `1..=100`.
+    pub fn new(percent: u8, window_ms: u32) -> Self {
+        let percent = percent.clamp(1, 100);
+
+        Self {
+            percent,
+            window_ms,
+            available: u64::from(window_ms) * u64::from(percent),
+            last_refill_ms: None,
+        }
+    }
+
+    pub fn percent(&self) -> u8 {
+        self.percent
+    }
+
+    pub fn window_ms(&self) -> u32 {
+        self.window_ms
+    }
+
+    fn capacity(&self) -> u64 {
+        u64::from(self.window_ms) * u64::from(self.percent)
+    }
+
+    fn refill(&mut self, now_ms: u64) {
+        let elapsed_ms = self
+            .last_refill_ms
+            .map_or(0, |last_refill_ms| now_ms.saturating_sub(last_refill_ms));
+        self.available =
+            (self.available + elapsed_ms * u64::from(self.percent)).min(self.capacity());
+        self.last_refill_ms = Some(now_ms);
+    }
+
+    /// Air time in milliseconds that can be spent right now.
+    pub(crate) fn remaining_ms(&mut self, now_ms: u64) -> u32 {
+        self.refill(now_ms);
+
+        (self.available / 100) as u32
+    }
+
+    /// Charges `air_time_ms` against the budget. If the budget doesn't cover it, nothing is
+    /// charged and the time in milliseconds until it will is returned, or `None` if the
+    /// transmission exceeds even a full budget.
+    pub(crate) fn try_consume(&mut self, air_time_ms: u32, now_ms: u64) -> Result<(), Option<u32>> {
+        self.refill(now_ms);
+
+        let cost = u64::from(air_time_ms) * 100;
+        if cost > self.capacity() {
+            return Err(None);
+        }
+        if cost > self.available {
+            let wait_ms = (cost - self.available).div_ceil(u64::from(self.percent));
+            return Err(Some(wait_ms as u32));
+        }
+
+        self.available -= cost;
+        Ok(())
+    }
+}
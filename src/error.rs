@@ -0,0 +1,107 @@
+use crate::BaudRate;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hc12Error {
+    Test,
+    BaudRate,
+    /// No baud rate got an answer from the module, with what the probes saw instead. A module
+    /// whose configuration is lost can be reset with
+    /// [`PoweredHc12::recover`](crate::PoweredHc12::recover).
+    AutoBaudRate(AutoBaudFailure),
+    TransmissionMode,
+    Default,
+    Config,
+    #[cfg(feature = "esp32c3")]
+    UartError(esp_hal::uart::Error),
+    InvalidResponse,
+    Timeout,
+    BufferFull,
+    FrameTooLarge,
+    Crc,
+    ChecksumMismatch,
+    Channel,
+    IncompatibleMode,
+    Addressing,
+    NoAck,
+    Decode,
+    TransmitPower,
+    DutyCycleExceeded,
+    Busy,
+    /// The module answered with `ERROR`: it understood the command but refused it.
+    ModuleRejected(crate::protocol::RejectedResponse),
+    /// I/O error of a port or pin other than esp-hal's, as used by
+    /// [`GenericHc12`](crate::GenericHc12).
+    Io,
+    /// The driver was constructed without a SET pin, so it can't enter command mode.
+    NoSetPin,
+    /// The module didn't confirm `AT+SLEEP`.
+    Sleep,
+}
+
+/// What the `AT` probes of `auto_baud` saw when none got the expected answer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AutoBaudFailure {
+    /// Nothing arrived at any baud rate: the module is unpowered, TX, RX or SET are miswired, or
+    /// SET doesn't reach the command level.
+    NoResponseAtAnyBaud,
+    /// Bytes arrived, but only framing errors or noise, e.g. from a module set to a data format
+    /// other than 8N1.
+    GarbageAtAllBauds,
+    /// At this baud rate the module answered with text containing `OK` or `ERROR`, but not the
+    /// plain `OK`: it is there, but its firmware or the line garbles the answer.
+    PartialResponseAt(BaudRate),
+}
+
+impl AutoBaudFailure {
+    /// What a failed `AT` probe at `baud_rate` tells about the module, judged from `error` and
+    /// the raw `response`.
+    pub(crate) fn classify(baud_rate: BaudRate, error: &Hc12Error, response: &[u8]) -> Self {
+        #[cfg(feature = "esp32c3")]
+        use esp_hal::uart::Error::{RxFrameError, RxGlitchDetected, RxParityError};
+
+        match error {
+            #[cfg(feature = "esp32c3")]
+            Hc12Error::UartError(RxFrameError | RxParityError | RxGlitchDetected) => {
+                Self::GarbageAtAllBauds
+            }
+            Hc12Error::ModuleRejected(_) => Self::PartialResponseAt(baud_rate),
+            _ if response.is_empty() => Self::NoResponseAtAnyBaud,
+            _ if response.windows(2).any(|window| window == b"OK") => {
+                Self::PartialResponseAt(baud_rate)
+            }
+            _ => Self::GarbageAtAllBauds,
+        }
+    }
+
+    /// Combines the evidence of two probes, keeping the more telling one.
+    pub(crate) fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::PartialResponseAt(_), _) => self,
+            (_, Self::PartialResponseAt(_)) => other,
+            (Self::GarbageAtAllBauds, _) | (_, Self::GarbageAtAllBauds) => Self::GarbageAtAllBauds,
+            _ => Self::NoResponseAtAnyBaud,
+        }
+    }
+
+    pub(crate) fn log(&self) {
+        match self {
+            Self::NoResponseAtAnyBaud => {
+                warn!("auto_baud failed: no response at any baud rate, check power and wiring")
+            }
+            Self::GarbageAtAllBauds => {
+                warn!("auto_baud failed: only garbage at all baud rates, check the data format")
+            }
+            Self::PartialResponseAt(baud_rate) => warn!(
+                "auto_baud failed: partial response at {} baud",
+                u32::from(baud_rate)
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "esp32c3")]
+impl From<esp_hal::uart::Error> for Hc12Error {
+    fn from(error: esp_hal::uart::Error) -> Self {
+        Hc12Error::UartError(error)
+    }
+}
@@ -0,0 +1,39 @@
+//! Internal logging macros dispatching to `defmt` or `log`, whichever feature is enabled. With
+//! both enabled `defmt` is used; with neither the arguments are evaluated and discarded.
+
+// Only the esp-hal driver traces so far.
+#[cfg(feature = "esp32c3")]
+macro_rules! trace {
+    ($s:literal $(, $x:expr)* $(,)?) => {{
+        #[cfg(feature = "defmt")]
+        ::defmt::trace!($s $(, $x)*);
+        #[cfg(all(feature = "log", not(feature = "defmt")))]
+        ::log::trace!($s $(, $x)*);
+        #[cfg(not(any(feature = "log", feature = "defmt")))]
+        let _ = ($(&$x),*);
+    }};
+}
+
+// Only the esp-hal driver logs at this level so far.
+#[cfg(feature = "esp32c3")]
+macro_rules! info {
+    ($s:literal $(, $x:expr)* $(,)?) => {{
+        #[cfg(feature = "defmt")]
+        ::defmt::info!($s $(, $x)*);
+        #[cfg(all(feature = "log", not(feature = "defmt")))]
+        ::log::info!($s $(, $x)*);
+        #[cfg(not(any(feature = "log", feature = "defmt")))]
+        let _ = ($(&$x),*);
+    }};
+}
+
+macro_rules! warn {
+    ($s:literal $(, $x:expr)* $(,)?) => {{
+        #[cfg(feature = "defmt")]
+        ::defmt::warn!($s $(, $x)*);
+        #[cfg(all(feature = "log", not(feature = "defmt")))]
+        ::log::warn!($s $(, $x)*);
+        #[cfg(not(any(feature = "log", feature = "defmt")))]
+        let _ = ($(&$x),*);
+    }};
+}
@@ -0,0 +1,320 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+#[cfg(feature = "async")]
+use esp_hal::Async;
+use esp_hal::{delay::Delay, gpio::OutputOpenDrain, Blocking, DriverMode};
+
+use crate::{FrameCodec, FrameInfo, Hc12Error, Hc12Framed, ReliablePolicy, BROADCAST_ADDRESS};
+
+/// Length of the `[message id, index, count, offset (u16 LE)]` header in front of every fragment.
+pub const FRAGMENT_HEADER_LEN: usize = 5;
+
+/// Time after which an incomplete message is discarded when no explicit window is set.
+pub const DEFAULT_REASSEMBLY_TIMEOUT_MS: u32 = 5_000;
+
+/// State of the message currently being reassembled.
+struct Reassembly {
+    source: Option<u8>,
+    destination: Option<u8>,
+    message_id: u8,
+    count: u8,
+    received: [u32; 8],
+    received_count: u8,
+    len: Option<usize>,
+    started_ms: u64,
+}
+
+impl Reassembly {
+    fn is_received(&self, index: u8) -> bool {
+        self.received[usize::from(index / 32)] & (1 << (index % 32)) != 0
+    }
+
+    fn mark_received(&mut self, index: u8) {
+        self.received[usize::from(index / 32)] |= 1 << (index % 32);
+        self.received_count += 1;
+    }
+}
+
+/// Wraps an [`Hc12Framed`] with a fragmentation layer for messages larger than a single frame.
+/// Outgoing messages are split into fragments of at most `fragment_len` bytes, each carrying a
+/// message id, its index, the fragment count and its offset in the message. The receiver places
+/// fragments by offset, so duplicated and reordered fragments are handled.
+///
+/// Only one message is reassembled at a time. Fragments of a new message from the same sender
+/// replace an unfinished one, fragments from other senders are dropped until the current message
+/// completes or its reassembly window expires. Messages are limited to 255 fragments and 64 KiB.
+pub struct Hc12Fragmented<
+    'd,
+    Dm: DriverMode,
+    C: FrameCodec,
+    const N: usize,
+    D = Delay,
+    S: OutputPin = OutputOpenDrain<'d>,
+> {
+    framed: Hc12Framed<'d, Dm, C, N, D, S>,
+    fragment_len: usize,
+    timeout_ms: u32,
+    reliable: Option<ReliablePolicy>,
+    message_id: u8,
+    reassembly: Option<Reassembly>,
+}
+
+impl<'d, Dm: DriverMode, C: FrameCodec, const N: usize, D, S: OutputPin>
+    Hc12Fragmented<'d, Dm, C, N, D, S>
+{
+    /// `fragment_len` is the number of message bytes per fragment; together with
+    /// [`FRAGMENT_HEADER_LEN`] and the framing overhead it has to fit into the `N` byte frame
+    /// buffer.
+    pub fn new(framed: Hc12Framed<'d, Dm, C, N, D, S>, fragment_len: usize) -> Self {
+        Self {
+            framed,
+            fragment_len: fragment_len.max(1),
+            timeout_ms: DEFAULT_REASSEMBLY_TIMEOUT_MS,
+            reliable: None,
+            message_id: 0,
+            reassembly: None,
+        }
+    }
+
+    pub fn timeout_ms(&self) -> u32 {
+        self.timeout_ms
+    }
+
+    /// Sets the window after the first received fragment in which a message has to complete
+    /// before it is discarded.
+    pub fn set_timeout_ms(&mut self, timeout_ms: u32) {
+        self.timeout_ms = timeout_ms;
+    }
+
+    pub fn reliable(&self) -> Option<ReliablePolicy> {
+        self.reliable
+    }
+
+    /// Sends every fragment with [`Hc12Framed::send_reliable`] using `policy`, or unacknowledged
+    /// with `None`.
+    pub fn set_reliable(&mut self, policy: Option<ReliablePolicy>) {
+        self.reliable = policy;
+    }
+
+    pub fn inner(&mut self) -> &mut Hc12Framed<'d, Dm, C, N, D, S> {
+        &mut self.framed
+    }
+
+    pub fn into_inner(self) -> Hc12Framed<'d, Dm, C, N, D, S> {
+        self.framed
+    }
+
+    /// Number of fragments `payload` is split into, and the id of its message.
+    fn start_message(&mut self, payload: &[u8]) -> Result<(u8, u8), Hc12Error> {
+        let count = payload.len().div_ceil(self.fragment_len).max(1);
+        if count > usize::from(u8::MAX) || payload.len() > usize::from(u16::MAX) {
+            return Err(Hc12Error::FrameTooLarge);
+        }
+
+        self.message_id = self.message_id.wrapping_add(1);
+        Ok((count as u8, self.message_id))
+    }
+
+    /// Builds fragment `index` of `payload` into `fragment` and returns its length.
+    fn build_fragment(
+        &self,
+        message_id: u8,
+        index: u8,
+        count: u8,
+        payload: &[u8],
+        fragment: &mut [u8; N],
+    ) -> Result<usize, Hc12Error> {
+        let offset = usize::from(index) * self.fragment_len;
+        let data = &payload[offset..payload.len().min(offset + self.fragment_len)];
+
+        let len = FRAGMENT_HEADER_LEN + data.len();
+        if len > N {
+            return Err(Hc12Error::FrameTooLarge);
+        }
+        let offset = (offset as u16).to_le_bytes();
+        fragment[..FRAGMENT_HEADER_LEN]
+            .copy_from_slice(&[message_id, index, count, offset[0], offset[1]]);
+        fragment[FRAGMENT_HEADER_LEN..len].copy_from_slice(data);
+
+        Ok(len)
+    }
+
+    /// Adds a received fragment to the message in `buffer` and returns the message's info once all
+    /// of its fragments have arrived.
+    fn accept_fragment(
+        &mut self,
+        frame_info: FrameInfo,
+        fragment: &[u8],
+        buffer: &mut [u8],
+        now_ms: u64,
+    ) -> Result<Option<FrameInfo>, Hc12Error> {
+        let Some((header, data)) = fragment.split_first_chunk::<FRAGMENT_HEADER_LEN>() else {
+            return Ok(None);
+        };
+        let [message_id, index, count, offset_low, offset_high] = *header;
+        if index >= count {
+            return Ok(None);
+        }
+        let offset = usize::from(u16::from_le_bytes([offset_low, offset_high]));
+
+        if self
+            .reassembly
+            .as_ref()
+            .is_some_and(|reassembly| now_ms - reassembly.started_ms > u64::from(self.timeout_ms))
+        {
+            self.reassembly = None;
+        }
+
+        let reassembly = match self.reassembly.take() {
+            Some(reassembly)
+                if reassembly.source == frame_info.source
+                    && reassembly.message_id == message_id
+                    && reassembly.count == count =>
+            {
+                reassembly
+            }
+            Some(reassembly) if reassembly.source != frame_info.source => {
+                self.reassembly = Some(reassembly);
+                return Ok(None);
+            }
+            _ => Reassembly {
+                source: frame_info.source,
+                destination: frame_info.destination,
+                message_id,
+                count,
+                received: [0; 8],
+                received_count: 0,
+                len: None,
+                started_ms: now_ms,
+            },
+        };
+        let reassembly = self.reassembly.insert(reassembly);
+
+        if reassembly.is_received(index) {
+            return Ok(None);
+        }
+
+        let end = offset + data.len();
+        if end > buffer.len() {
+            self.reassembly = None;
+            return Err(Hc12Error::BufferFull);
+        }
+        buffer[offset..end].copy_from_slice(data);
+        reassembly.mark_received(index);
+        if index == count - 1 {
+            reassembly.len = Some(end);
+        }
+
+        if reassembly.received_count < count {
+            return Ok(None);
+        }
+
+        let message_info = FrameInfo {
+            len: reassembly.len.unwrap_or(end),
+            source: reassembly.source,
+            destination: reassembly.destination,
+        };
+        self.reassembly = None;
+
+        Ok(Some(message_info))
+    }
+}
+
+impl<C: FrameCodec, const N: usize, D: DelayNs, S: OutputPin>
+    Hc12Fragmented<'_, Blocking, C, N, D, S>
+{
+    /// Sends `payload` to all nodes.
+    pub fn send_message(&mut self, payload: &[u8]) -> Result<(), Hc12Error> {
+        self.send_message_to(BROADCAST_ADDRESS, payload)
+    }
+
+    /// Sends `payload` in fragments to the node with address `destination`.
+    pub fn send_message_to(&mut self, destination: u8, payload: &[u8]) -> Result<(), Hc12Error> {
+        let (count, message_id) = self.start_message(payload)?;
+        let mut fragment = [0u8; N];
+
+        for index in 0..count {
+            let len = self.build_fragment(message_id, index, count, payload, &mut fragment)?;
+            match &self.reliable {
+                Some(policy) => self
+                    .framed
+                    .send_reliable(destination, &fragment[..len], policy)?,
+                None => self.framed.send_frame_to(destination, &fragment[..len])?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Blocks until a complete message has been reassembled in `buffer`. Messages larger than
+    /// `buffer` are discarded with [`Hc12Error::BufferFull`]. After any other error the next call
+    /// continues the reassembly, so it must be given the same buffer.
+    pub fn recv_message(&mut self, buffer: &mut [u8]) -> Result<FrameInfo, Hc12Error> {
+        let mut fragment = [0u8; N];
+
+        loop {
+            let frame_info = self.framed.recv_frame(&mut fragment)?;
+            let now_ms = esp_hal::time::now().duration_since_epoch().to_millis();
+
+            if let Some(message_info) =
+                self.accept_fragment(frame_info, &fragment[..frame_info.len], buffer, now_ms)?
+            {
+                return Ok(message_info);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<C: FrameCodec, const N: usize, D, S: OutputPin> Hc12Fragmented<'_, Async, C, N, D, S> {
+    /// Sends `payload` to all nodes.
+    pub async fn send_message(&mut self, payload: &[u8]) -> Result<(), Hc12Error> {
+        self.send_message_to(BROADCAST_ADDRESS, payload).await
+    }
+
+    /// Sends `payload` in fragments to the node with address `destination`.
+    pub async fn send_message_to(
+        &mut self,
+        destination: u8,
+        payload: &[u8],
+    ) -> Result<(), Hc12Error> {
+        let (count, message_id) = self.start_message(payload)?;
+        let mut fragment = [0u8; N];
+
+        for index in 0..count {
+            let len = self.build_fragment(message_id, index, count, payload, &mut fragment)?;
+            match &self.reliable {
+                Some(policy) => {
+                    self.framed
+                        .send_reliable(destination, &fragment[..len], policy)
+                        .await?
+                }
+                None => {
+                    self.framed
+                        .send_frame_to(destination, &fragment[..len])
+                        .await?
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Waits until a complete message has been reassembled in `buffer`. Messages larger than
+    /// `buffer` are discarded with [`Hc12Error::BufferFull`]. After any other error the next call
+    /// continues the reassembly, so it must be given the same buffer.
+    pub async fn recv_message(&mut self, buffer: &mut [u8]) -> Result<FrameInfo, Hc12Error> {
+        let mut fragment = [0u8; N];
+
+        loop {
+            let frame_info = self.framed.recv_frame(&mut fragment).await?;
+            let now_ms = embassy_time::Instant::now().as_millis();
+
+            if let Some(message_info) =
+                self.accept_fragment(frame_info, &fragment[..frame_info.len], buffer, now_ms)?
+            {
+                return Ok(message_info);
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+/// Bytes preceding every payload read by [`FrameReader`]: start byte and length byte.
+pub const FRAME_READER_HEADER_LEN: usize = 2;
+
+/// Reassembles `[START, LEN, payload..]` frames from chunks of received bytes as they come out of
+/// any read, independent of how the bytes were read or where reads split the frames. Holds at most
+/// `N` bytes, so payloads are limited to `N - 2` bytes (and 255). Bytes that don't belong to a
+/// frame are skipped by searching for the next start byte.
+pub struct FrameReader<const N: usize> {
+    start_byte: u8,
+    buffer: [u8; N],
+    len: usize,
+    /// Length of the frame returned by the last `next_frame`, dropped on the next call.
+    returned: usize,
+    discarded: u32,
+}
+
+impl<const N: usize> FrameReader<N> {
+    pub const fn new(start_byte: u8) -> Self {
+        Self {
+            start_byte,
+            buffer: [0u8; N],
+            len: 0,
+            returned: 0,
+            discarded: 0,
+        }
+    }
+
+    pub fn start_byte(&self) -> u8 {
+        self.start_byte
+    }
+
+    /// Appends as much of `bytes` as fits and returns how many bytes were taken. Calling
+    /// [`FrameReader::next_frame`] until it returns `None` makes room again.
+    pub fn push(&mut self, bytes: &[u8]) -> usize {
+        self.drop_returned();
+
+        let len = bytes.len().min(N - self.len);
+        self.buffer[self.len..self.len + len].copy_from_slice(&bytes[..len]);
+        self.len += len;
+
+        len
+    }
+
+    /// The payload of the next complete frame, or `None` until more bytes are pushed. The payload
+    /// stays valid until the next call to `push` or `next_frame`.
+    pub fn next_frame(&mut self) -> Option<&[u8]> {
+        self.drop_returned();
+
+        loop {
+            let Some(start) = self.buffer[..self.len]
+                .iter()
+                .position(|&byte| byte == self.start_byte)
+            else {
+                self.discard(self.len);
+                return None;
+            };
+            self.discard(start);
+
+            if self.len < FRAME_READER_HEADER_LEN {
+                return None;
+            }
+
+            let frame_len = FRAME_READER_HEADER_LEN + usize::from(self.buffer[1]);
+            if frame_len > N {
+                // Can never be completed, so the start byte was part of garbage.
+                self.discard(1);
+                continue;
+            }
+
+            if self.len < frame_len {
+                return None;
+            }
+
+            self.returned = frame_len;
+            return Some(&self.buffer[FRAME_READER_HEADER_LEN..frame_len]);
+        }
+    }
+
+    /// Number of bytes skipped because they were not part of a frame.
+    pub fn discarded(&self) -> u32 {
+        self.discarded
+    }
+
+    pub fn reset_discarded(&mut self) {
+        self.discarded = 0;
+    }
+
+    /// Drops all buffered bytes, e.g. after reconfiguring the link.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.returned = 0;
+    }
+
+    fn drop_returned(&mut self) {
+        let returned = core::mem::take(&mut self.returned);
+        self.buffer.copy_within(returned..self.len, 0);
+        self.len -= returned;
+    }
+
+    fn discard(&mut self, len: usize) {
+        self.buffer.copy_within(len..self.len, 0);
+        self.len -= len;
+        self.discarded += len as u32;
+    }
+}
@@ -0,0 +1,1515 @@
+// The link layer below is only driven by `Hc12Framed`; without esp32c3 just the tests use it.
+#![cfg_attr(not(feature = "esp32c3"), allow(dead_code))]
+
+#[cfg(feature = "esp32c3")]
+use core::ops::RangeInclusive;
+
+#[cfg(all(feature = "esp32c3", feature = "async"))]
+use embassy_time::{with_timeout, Duration, Timer};
+#[cfg(feature = "esp32c3")]
+use embedded_hal::delay::DelayNs;
+#[cfg(feature = "esp32c3")]
+use embedded_hal::digital::OutputPin;
+#[cfg(all(feature = "esp32c3", feature = "async"))]
+use esp_hal::Async;
+#[cfg(feature = "esp32c3")]
+use esp_hal::{delay::Delay, gpio::OutputOpenDrain, rng::Rng, Blocking, DriverMode};
+
+#[cfg(feature = "esp32c3")]
+use crate::Hc12;
+use crate::Hc12Error;
+
+pub const FRAME_SYNC: u8 = 0xAA;
+
+/// Bytes preceding every payload: sync byte, flags byte and length byte.
+pub const FRAME_HEADER_LEN: usize = 3;
+
+const FLAGS_CHECKSUM_MASK: u8 = 0b0000_0011;
+/// Flag bits with a defined meaning; frames with any other bit set are rejected.
+const FLAGS_KNOWN_MASK: u8 = FLAGS_CHECKSUM_MASK;
+
+/// Checksum appended to every frame. The algorithm is recorded in the frame header so a peer
+/// configured with a different algorithm reports [`Hc12Error::ChecksumMismatch`] instead of
+/// accepting the frame.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum Checksum {
+    /// CRC-8/SMBUS (polynomial 0x07), 1 byte.
+    Crc8,
+    /// CRC-16/X25, 2 bytes.
+    #[default]
+    Crc16,
+    /// CRC-32/ISO-HDLC, 4 bytes.
+    Crc32,
+}
+
+impl Checksum {
+    pub const fn size(&self) -> usize {
+        match self {
+            Checksum::Crc8 => 1,
+            Checksum::Crc16 => 2,
+            Checksum::Crc32 => 4,
+        }
+    }
+
+    const fn flags(&self) -> u8 {
+        match self {
+            Checksum::Crc8 => 0,
+            Checksum::Crc16 => 1,
+            Checksum::Crc32 => 2,
+        }
+    }
+
+    const fn from_flags(flags: u8) -> Option<Self> {
+        match flags & FLAGS_CHECKSUM_MASK {
+            0 => Some(Checksum::Crc8),
+            1 => Some(Checksum::Crc16),
+            2 => Some(Checksum::Crc32),
+            _ => None,
+        }
+    }
+
+    /// Computes the checksum over the frame header (without sync byte) followed by the payload.
+    fn compute(&self, header: &[u8], payload: &[u8]) -> u32 {
+        match self {
+            Checksum::Crc8 => u32::from(crc8_update(crc8_update(0, header), payload)),
+            Checksum::Crc16 => {
+                u32::from(crc16_update(crc16_update(0xFFFF, header), payload) ^ 0xFFFF)
+            }
+            Checksum::Crc32 => {
+                crc32_update(crc32_update(0xFFFF_FFFF, header), payload) ^ 0xFFFF_FFFF
+            }
+        }
+    }
+}
+
+/// CRC-8/SMBUS (polynomial 0x07, init 0x00, no final xor).
+pub fn crc8(data: &[u8]) -> u8 {
+    crc8_update(0, data)
+}
+
+/// CRC-16/X25 (reflected polynomial 0x1021, init 0xFFFF, final xor 0xFFFF).
+pub fn crc16(data: &[u8]) -> u16 {
+    crc16_update(0xFFFF, data) ^ 0xFFFF
+}
+
+/// CRC-32/ISO-HDLC (reflected polynomial 0x04C11DB7, init 0xFFFFFFFF, final xor 0xFFFFFFFF).
+pub fn crc32(data: &[u8]) -> u32 {
+    crc32_update(0xFFFF_FFFF, data) ^ 0xFFFF_FFFF
+}
+
+fn crc8_update(mut crc: u8, data: &[u8]) -> u8 {
+    for byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn crc16_update(mut crc: u16, data: &[u8]) -> u16 {
+    for byte in data {
+        crc ^= u16::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0x8408
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+/// Wire format used by [`Hc12Framed`]. Implementations encode whole payloads and decode the
+/// received byte stream incrementally, one byte at a time.
+pub trait FrameCodec {
+    /// Encodes `payload` into `out` and returns the number of bytes written.
+    fn encode(&mut self, payload: &[u8], out: &mut [u8]) -> Result<usize, Hc12Error>;
+
+    /// Feeds one received byte into the decoder and returns the payload once a complete frame has
+    /// been received. Frames the codec rejects are reported as errors; the decoder then continues
+    /// with the following bytes.
+    fn decode(&mut self, byte: u8) -> Option<Result<&[u8], Hc12Error>>;
+
+    /// Number of received bytes that were dropped because they were not part of a valid frame.
+    fn discarded(&self) -> u32 {
+        0
+    }
+
+    fn reset_discarded(&mut self) {}
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DecoderState {
+    Sync,
+    Flags,
+    Length,
+    Payload,
+    Checksum,
+}
+
+/// Codec for `[SYNC, FLAGS, LEN, payload.., checksum LE]` frames carrying at most `N` payload
+/// bytes (at most 255). The receiver resynchronizes on the sync byte after corrupted data.
+pub struct LengthCrcCodec<const N: usize> {
+    checksum: Checksum,
+    state: DecoderState,
+    buffer: [u8; N],
+    flags: u8,
+    length: usize,
+    received: usize,
+    received_checksum: [u8; 4],
+    discarded: u32,
+}
+
+impl<const N: usize> Default for LengthCrcCodec<N> {
+    fn default() -> Self {
+        Self::new(Checksum::default())
+    }
+}
+
+impl<const N: usize> LengthCrcCodec<N> {
+    pub const fn new(checksum: Checksum) -> Self {
+        Self {
+            checksum,
+            state: DecoderState::Sync,
+            buffer: [0u8; N],
+            flags: 0,
+            length: 0,
+            received: 0,
+            received_checksum: [0u8; 4],
+            discarded: 0,
+        }
+    }
+
+    pub fn checksum(&self) -> Checksum {
+        self.checksum
+    }
+
+    pub fn set_checksum(&mut self, checksum: Checksum) {
+        self.checksum = checksum;
+    }
+
+    /// Drops the `consumed` header bytes preceding the rejected `byte`, which may itself be the
+    /// sync byte of the next frame.
+    fn resync(&mut self, byte: u8, consumed: u32) {
+        if byte == FRAME_SYNC {
+            self.discarded += consumed;
+            self.state = DecoderState::Flags;
+        } else {
+            self.discarded += consumed + 1;
+            self.state = DecoderState::Sync;
+        }
+    }
+}
+
+impl<const N: usize> FrameCodec for LengthCrcCodec<N> {
+    fn encode(&mut self, payload: &[u8], out: &mut [u8]) -> Result<usize, Hc12Error> {
+        if payload.len() > N || payload.len() > usize::from(u8::MAX) {
+            return Err(Hc12Error::FrameTooLarge);
+        }
+
+        let frame_len = FRAME_HEADER_LEN + payload.len() + self.checksum.size();
+        if frame_len > out.len() {
+            return Err(Hc12Error::BufferFull);
+        }
+
+        let header = [FRAME_SYNC, self.checksum.flags(), payload.len() as u8];
+        let checksum = self.checksum.compute(&header[1..], payload).to_le_bytes();
+
+        out[..FRAME_HEADER_LEN].copy_from_slice(&header);
+        out[FRAME_HEADER_LEN..FRAME_HEADER_LEN + payload.len()].copy_from_slice(payload);
+        out[FRAME_HEADER_LEN + payload.len()..frame_len]
+            .copy_from_slice(&checksum[..self.checksum.size()]);
+
+        Ok(frame_len)
+    }
+
+    /// A complete frame failing its checksum is reported as [`Hc12Error::Crc`], an intact frame
+    /// protected by a different checksum than the configured one as
+    /// [`Hc12Error::ChecksumMismatch`].
+    fn decode(&mut self, byte: u8) -> Option<Result<&[u8], Hc12Error>> {
+        match self.state {
+            DecoderState::Sync => {
+                if byte == FRAME_SYNC {
+                    self.state = DecoderState::Flags;
+                } else {
+                    self.discarded += 1;
+                }
+            }
+            DecoderState::Flags => {
+                if byte & !FLAGS_KNOWN_MASK == 0 && Checksum::from_flags(byte).is_some() {
+                    self.flags = byte;
+                    self.state = DecoderState::Length;
+                } else {
+                    self.resync(byte, 1);
+                }
+            }
+            DecoderState::Length => {
+                let length = usize::from(byte);
+                if length > N {
+                    self.resync(byte, 2);
+                } else {
+                    self.length = length;
+                    self.received = 0;
+                    self.state = if length == 0 {
+                        DecoderState::Checksum
+                    } else {
+                        DecoderState::Payload
+                    };
+                }
+            }
+            DecoderState::Payload => {
+                self.buffer[self.received] = byte;
+                self.received += 1;
+                if self.received == self.length {
+                    self.received = 0;
+                    self.state = DecoderState::Checksum;
+                }
+            }
+            DecoderState::Checksum => {
+                // Flags were validated when they were received.
+                let checksum = Checksum::from_flags(self.flags).unwrap();
+                self.received_checksum[self.received] = byte;
+                self.received += 1;
+                if self.received == checksum.size() {
+                    self.state = DecoderState::Sync;
+
+                    let expected = checksum.compute(
+                        &[self.flags, self.length as u8],
+                        &self.buffer[..self.length],
+                    );
+                    if u32::from_le_bytes(self.received_checksum) & checksum_mask(checksum.size())
+                        != expected
+                    {
+                        self.discarded += (FRAME_HEADER_LEN + self.length + checksum.size()) as u32;
+                        return Some(Err(Hc12Error::Crc));
+                    }
+
+                    if checksum != self.checksum {
+                        return Some(Err(Hc12Error::ChecksumMismatch));
+                    }
+
+                    return Some(Ok(&self.buffer[..self.length]));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn discarded(&self) -> u32 {
+        self.discarded
+    }
+
+    fn reset_discarded(&mut self) {
+        self.discarded = 0;
+    }
+}
+
+fn checksum_mask(len: usize) -> u32 {
+    if len == 4 {
+        u32::MAX
+    } else {
+        (1 << (8 * len)) - 1
+    }
+}
+
+/// Splits a payload into COBS blocks. Every item is a code byte followed by the data bytes it
+/// covers; the terminating zero delimiter is not included.
+struct CobsBlocks<'a> {
+    rest: &'a [u8],
+    done: bool,
+}
+
+impl<'a> Iterator for CobsBlocks<'a> {
+    type Item = (u8, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let rest = self.rest;
+        let window = &rest[..rest.len().min(254)];
+        match window.iter().position(|&byte| byte == 0) {
+            Some(index) => {
+                self.rest = &rest[index + 1..];
+                Some((index as u8 + 1, &rest[..index]))
+            }
+            None if window.len() == 254 => {
+                self.rest = &rest[254..];
+                Some((0xFF, window))
+            }
+            None => {
+                self.done = true;
+                Some((window.len() as u8 + 1, window))
+            }
+        }
+    }
+}
+
+fn cobs_blocks(payload: &[u8]) -> CobsBlocks<'_> {
+    CobsBlocks {
+        rest: payload,
+        done: false,
+    }
+}
+
+/// Maximum length of the COBS encoding of a `payload_len` byte payload, including the trailing
+/// zero delimiter.
+pub const fn cobs_max_encoded_len(payload_len: usize) -> usize {
+    payload_len + payload_len / 254 + 2
+}
+
+/// Maximum length of a frame [`CobsCodec`] encodes from a `payload_len` byte payload: the COBS
+/// encoding of [`cobs_max_encoded_len`] plus the leading delimiter. Use it to size the transmit
+/// buffer of [`Hc12Framed`].
+pub const fn cobs_codec_max_frame_len(payload_len: usize) -> usize {
+    cobs_max_encoded_len(payload_len) + 1
+}
+
+/// COBS-encodes `payload` into `output` followed by a zero delimiter and returns the number of
+/// bytes written.
+pub fn cobs_encode(payload: &[u8], output: &mut [u8]) -> Result<usize, Hc12Error> {
+    let mut len = 0;
+    for (code, data) in cobs_blocks(payload) {
+        if len + 1 + data.len() >= output.len() {
+            return Err(Hc12Error::BufferFull);
+        }
+        output[len] = code;
+        output[len + 1..len + 1 + data.len()].copy_from_slice(data);
+        len += 1 + data.len();
+    }
+    output[len] = 0;
+
+    Ok(len + 1)
+}
+
+/// COBS codec for zero-delimited frames carrying at most `N` payload bytes. The receiver
+/// resynchronizes on the next zero delimiter after any corruption. COBS frames carry no checksum.
+pub struct CobsCodec<const N: usize> {
+    buffer: [u8; N],
+    length: usize,
+    block_remaining: u8,
+    pending_zero: bool,
+    received: usize,
+    overflow: bool,
+    discarded: u32,
+}
+
+impl<const N: usize> Default for CobsCodec<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> CobsCodec<N> {
+    pub const fn new() -> Self {
+        Self {
+            buffer: [0u8; N],
+            length: 0,
+            block_remaining: 0,
+            pending_zero: false,
+            received: 0,
+            overflow: false,
+            discarded: 0,
+        }
+    }
+
+    fn restart(&mut self) {
+        self.length = 0;
+        self.block_remaining = 0;
+        self.pending_zero = false;
+        self.received = 0;
+        self.overflow = false;
+    }
+
+    fn append(&mut self, byte: u8) {
+        if self.length == N {
+            self.overflow = true;
+        } else {
+            self.buffer[self.length] = byte;
+            self.length += 1;
+        }
+    }
+}
+
+impl<const N: usize> FrameCodec for CobsCodec<N> {
+    fn encode(&mut self, payload: &[u8], out: &mut [u8]) -> Result<usize, Hc12Error> {
+        if payload.len() > N {
+            return Err(Hc12Error::FrameTooLarge);
+        }
+        if out.is_empty() {
+            return Err(Hc12Error::BufferFull);
+        }
+
+        // A leading delimiter terminates any noise the receiver picked up since the last frame.
+        out[0] = 0;
+        Ok(1 + cobs_encode(payload, &mut out[1..])?)
+    }
+
+    fn decode(&mut self, byte: u8) -> Option<Result<&[u8], Hc12Error>> {
+        if byte == 0 {
+            let received = self.received;
+            let complete = self.block_remaining == 0 && !self.overflow;
+            let length = self.length;
+            self.restart();
+
+            if received == 0 {
+                return None;
+            }
+            if !complete {
+                self.discarded += received as u32;
+                return None;
+            }
+            return Some(Ok(&self.buffer[..length]));
+        }
+
+        self.received += 1;
+        if self.block_remaining == 0 {
+            if self.pending_zero {
+                self.append(0);
+            }
+            self.block_remaining = byte - 1;
+            self.pending_zero = byte != 0xFF;
+        } else {
+            self.append(byte);
+            self.block_remaining -= 1;
+        }
+
+        None
+    }
+
+    fn discarded(&self) -> u32 {
+        self.discarded
+    }
+
+    fn reset_discarded(&mut self) {
+        self.discarded = 0;
+    }
+}
+
+/// Destination address accepted by every node.
+pub const BROADCAST_ADDRESS: u8 = 0xFF;
+
+/// Length of the `[destination, source, control, sequence]` link header carried in front of the
+/// payload when addressing is enabled.
+const LINK_HEADER_LEN: usize = 4;
+
+/// The sender expects an acknowledgement for this frame.
+const CONTROL_RELIABLE: u8 = 0b0000_0001;
+/// The frame acknowledges the frame with the same sequence number.
+const CONTROL_ACK: u8 = 0b0000_0010;
+/// The sequence number counts the sender's frames and feeds the receiver's [`LinkStats`].
+const CONTROL_SEQUENCED: u8 = 0b0000_0100;
+/// The sequence number is the first the sender used since it started, e.g. after a reboot.
+const CONTROL_RESTART: u8 = 0b0000_1000;
+
+/// Number of peers whose last sequence number is remembered for duplicate suppression and loss
+/// statistics.
+const DUPLICATE_HISTORY: usize = 8;
+
+#[derive(Clone, Copy)]
+struct LinkHeader {
+    destination: u8,
+    source: u8,
+    control: u8,
+    sequence: u8,
+}
+
+#[derive(Clone, Copy)]
+struct PeerSequence {
+    source: u8,
+    sequence: u8,
+    restart: bool,
+}
+
+/// Last sequence number received from each of the [`DUPLICATE_HISTORY`] most recent senders.
+struct SequenceHistory {
+    peers: [Option<PeerSequence>; DUPLICATE_HISTORY],
+    next_slot: usize,
+}
+
+impl SequenceHistory {
+    const fn new() -> Self {
+        Self {
+            peers: [None; DUPLICATE_HISTORY],
+            next_slot: 0,
+        }
+    }
+
+    /// Records the sequence number of a received frame and returns whether the same frame was
+    /// received before. Sequenced frames also update `link_stats`.
+    ///
+    /// A frame with the restart marker starts the sender's count over, so it is only a duplicate
+    /// if it repeats the restart frame recorded last. A sender that reboots before its second
+    /// frame can still have its next first frame taken for a retransmission.
+    fn track(&mut self, header: &LinkHeader, link_stats: &mut LinkStats) -> bool {
+        let sequenced = header.control & CONTROL_SEQUENCED != 0;
+        let restart = header.control & CONTROL_RESTART != 0;
+        let entry = self
+            .peers
+            .iter_mut()
+            .flatten()
+            .find(|peer| peer.source == header.source);
+
+        let Some(peer) = entry else {
+            self.peers[self.next_slot] = Some(PeerSequence {
+                source: header.source,
+                sequence: header.sequence,
+                restart,
+            });
+            self.next_slot = (self.next_slot + 1) % DUPLICATE_HISTORY;
+            return false;
+        };
+
+        let step = header.sequence.wrapping_sub(peer.sequence);
+        if step == 0 && restart == peer.restart {
+            if sequenced {
+                link_stats.duplicated += 1;
+            }
+            return true;
+        }
+
+        // A large step is a reordered frame or a restarted sender rather than a loss.
+        if sequenced && !restart && (1..0x80).contains(&step) {
+            link_stats.lost += u32::from(step - 1);
+        }
+        peer.sequence = header.sequence;
+        peer.restart = restart;
+
+        false
+    }
+}
+
+/// Metadata of a received frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameInfo {
+    /// Payload length in bytes.
+    pub len: usize,
+    /// Sender of the frame, if addressing is enabled.
+    pub source: Option<u8>,
+    /// Destination of the frame (the local or the broadcast address), if addressing is enabled.
+    pub destination: Option<u8>,
+}
+
+/// Link quality counters of an [`Hc12Framed`] since creation or the last
+/// [`Hc12Framed::reset_link_stats`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LinkStats {
+    /// Frames addressed to this node that passed the codec's checks, including duplicates.
+    pub received: u32,
+    /// Sequenced frames missing between received ones.
+    pub lost: u32,
+    /// Sequenced frames received more than once.
+    pub duplicated: u32,
+    /// Frames dropped with [`Hc12Error::Crc`].
+    pub crc_failures: u32,
+}
+
+/// Retransmission policy of [`Hc12Framed::send_reliable`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReliablePolicy {
+    /// Number of retransmissions after the first attempt.
+    pub retries: u8,
+    /// Time to wait for the acknowledgement of each attempt.
+    pub ack_timeout_ms: u32,
+    /// Pause between an unacknowledged attempt and the next one.
+    pub retry_spacing_ms: u32,
+}
+
+impl Default for ReliablePolicy {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            ack_timeout_ms: 500,
+            retry_spacing_ms: 100,
+        }
+    }
+}
+
+#[cfg(feature = "esp32c3")]
+/// Random delay before every transmitted frame, so that nodes reporting at the same time don't
+/// keep colliding. The HC-12 has no carrier sense, so spreading transmissions is the only defense.
+#[derive(Clone, Copy)]
+pub struct TxJitter {
+    rng: Rng,
+    range_ms: (u32, u32),
+}
+
+#[cfg(feature = "esp32c3")]
+impl TxJitter {
+    /// Waits a uniformly drawn number of milliseconds from `range_ms` before each frame.
+    pub fn new(rng: Rng, range_ms: RangeInclusive<u32>) -> Self {
+        Self {
+            rng,
+            range_ms: (*range_ms.start(), *range_ms.end().max(range_ms.start())),
+        }
+    }
+
+    fn next_ms(&mut self) -> u32 {
+        let (start, end) = self.range_ms;
+        let span = end - start;
+        if span == u32::MAX {
+            return self.rng.random();
+        }
+
+        start + self.rng.random() % (span + 1)
+    }
+}
+
+#[cfg(feature = "esp32c3")]
+/// Wraps an [`Hc12`] with a framing layer using the wire format of codec `C`. `N` is the size of
+/// the transmit buffer and bounds the length of an encoded frame. Keeping `N` at or below
+/// [`TransmissionMode::max_burst`](crate::TransmissionMode::max_burst) of the mode in use sends
+/// every frame as a single packet.
+///
+/// With a local address configured, every frame carries a destination and source address and
+/// received frames addressed to other nodes are dropped before they reach the application.
+/// Addressing also enables acknowledged delivery through [`Hc12Framed::send_reliable`] and, with
+/// sequence numbers turned on, loss and duplicate tracking per sender in [`Hc12Framed::link_stats`].
+///
+/// After every frame the transmitter waits until the module has sent it over the air before the
+/// next frame may follow; see [`Hc12Framed::set_pacing`]. An optional random delay before each
+/// frame reduces collisions between nodes; see [`Hc12Framed::set_tx_jitter`].
+pub struct Hc12Framed<
+    'd,
+    Dm: DriverMode,
+    C: FrameCodec,
+    const N: usize,
+    D = Delay,
+    S: OutputPin = OutputOpenDrain<'d>,
+> {
+    hc12: Hc12<'d, Dm, D, S>,
+    codec: C,
+    address: Option<u8>,
+    sequence: u8,
+    restarted: bool,
+    sequence_numbers: bool,
+    received_sequences: SequenceHistory,
+    link_stats: LinkStats,
+    pacing: bool,
+    tx_jitter: Option<TxJitter>,
+    payload_buffer: [u8; N],
+    tx_buffer: [u8; N],
+    rx_buffer: [u8; 32],
+    rx_start: usize,
+    rx_end: usize,
+}
+
+#[cfg(feature = "esp32c3")]
+impl<'d, Dm: DriverMode, C: FrameCodec, const N: usize, D, S: OutputPin>
+    Hc12Framed<'d, Dm, C, N, D, S>
+{
+    pub fn new(hc12: Hc12<'d, Dm, D, S>, codec: C) -> Self {
+        Self {
+            hc12,
+            codec,
+            address: None,
+            sequence: 0,
+            restarted: true,
+            sequence_numbers: false,
+            received_sequences: SequenceHistory::new(),
+            link_stats: LinkStats::default(),
+            pacing: true,
+            tx_jitter: None,
+            payload_buffer: [0u8; N],
+            tx_buffer: [0u8; N],
+            rx_buffer: [0u8; 32],
+            rx_start: 0,
+            rx_end: 0,
+        }
+    }
+
+    /// Enables addressing with `address` as the local node address.
+    pub fn with_address(mut self, address: u8) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    pub fn address(&self) -> Option<u8> {
+        self.address
+    }
+
+    pub fn set_address(&mut self, address: Option<u8>) {
+        self.address = address;
+    }
+
+    pub fn sequence_numbers(&self) -> bool {
+        self.sequence_numbers
+    }
+
+    /// Stamps every outgoing frame with an incrementing sequence number so receivers can count
+    /// lost and duplicated frames. Requires addressing.
+    pub fn set_sequence_numbers(&mut self, enabled: bool) {
+        self.sequence_numbers = enabled;
+    }
+
+    pub fn link_stats(&self) -> LinkStats {
+        self.link_stats
+    }
+
+    pub fn reset_link_stats(&mut self) {
+        self.link_stats = LinkStats::default();
+    }
+
+    pub fn pacing(&self) -> bool {
+        self.pacing
+    }
+
+    /// Enables or disables the pause after each transmitted frame covering its air time at the
+    /// current mode and baud rate (and the minimum packet spacing of FU2). Enabled by default.
+    pub fn set_pacing(&mut self, pacing: bool) {
+        self.pacing = pacing;
+    }
+
+    /// Sets the random delay applied before every frame sent with `send_frame`, `send_frame_to`
+    /// and each attempt of `send_reliable`, or disables it with `None`. Acknowledgements are sent
+    /// without delay. Disabled by default.
+    pub fn set_tx_jitter(&mut self, tx_jitter: Option<TxJitter>) {
+        self.tx_jitter = tx_jitter;
+    }
+
+    fn tx_jitter_ms(&mut self) -> u32 {
+        self.tx_jitter.as_mut().map_or(0, TxJitter::next_ms)
+    }
+
+    pub fn inner(&mut self) -> &mut Hc12<'d, Dm, D, S> {
+        &mut self.hc12
+    }
+
+    pub fn into_inner(self) -> Hc12<'d, Dm, D, S> {
+        self.hc12
+    }
+
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    pub fn codec_mut(&mut self) -> &mut C {
+        &mut self.codec
+    }
+
+    /// Number of received bytes that were dropped because they were not part of a valid frame.
+    pub fn discarded(&self) -> u32 {
+        self.codec.discarded()
+    }
+
+    pub fn reset_discarded(&mut self) {
+        self.codec.reset_discarded();
+    }
+
+    /// Encodes `payload` for `destination` into the transmit buffer and returns the frame length.
+    fn encode(
+        &mut self,
+        destination: u8,
+        control: u8,
+        sequence: u8,
+        payload: &[u8],
+    ) -> Result<usize, Hc12Error> {
+        let Some(source) = self.address else {
+            return self.codec.encode(payload, &mut self.tx_buffer);
+        };
+
+        let len = LINK_HEADER_LEN + payload.len();
+        if len > N {
+            return Err(Hc12Error::FrameTooLarge);
+        }
+        self.payload_buffer[..LINK_HEADER_LEN].copy_from_slice(&[
+            destination,
+            source,
+            control,
+            sequence,
+        ]);
+        self.payload_buffer[LINK_HEADER_LEN..len].copy_from_slice(payload);
+
+        self.codec
+            .encode(&self.payload_buffer[..len], &mut self.tx_buffer)
+    }
+
+    /// Control byte and sequence number of the next unreliable frame.
+    fn next_header(&mut self) -> (u8, u8) {
+        if !self.sequence_numbers {
+            return (0, 0);
+        }
+
+        let restart = self.next_sequence();
+        (CONTROL_SEQUENCED | restart, self.sequence)
+    }
+
+    /// Advances the sequence number and returns the restart marker if it is the first one used.
+    fn next_sequence(&mut self) -> u8 {
+        self.sequence = self.sequence.wrapping_add(1);
+        if core::mem::take(&mut self.restarted) {
+            CONTROL_RESTART
+        } else {
+            0
+        }
+    }
+
+    /// Checks that reliable delivery to `destination` is possible and returns the control byte and
+    /// sequence number to use.
+    fn start_reliable(&mut self, destination: u8) -> Result<(u8, u8), Hc12Error> {
+        if self.address.is_none() || destination == BROADCAST_ADDRESS {
+            return Err(Hc12Error::Addressing);
+        }
+
+        let control = if self.sequence_numbers {
+            CONTROL_RELIABLE | CONTROL_SEQUENCED
+        } else {
+            CONTROL_RELIABLE
+        };
+        let control = control | self.next_sequence();
+
+        Ok((control, self.sequence))
+    }
+
+    /// Feeds buffered bytes into the codec until a frame addressed to this node is complete. Its
+    /// payload is left in `payload_buffer` and its length returned.
+    fn decode_buffered(&mut self) -> Option<Result<(Option<LinkHeader>, usize), Hc12Error>> {
+        while self.rx_start < self.rx_end {
+            let byte = self.rx_buffer[self.rx_start];
+            self.rx_start += 1;
+
+            let Some(result) = self.codec.decode(byte) else {
+                continue;
+            };
+            let payload = match result {
+                Ok(payload) => payload,
+                Err(error) => {
+                    if error == Hc12Error::Crc {
+                        self.link_stats.crc_failures += 1;
+                    }
+                    return Some(Err(error));
+                }
+            };
+
+            let (header, payload) = match self.address {
+                None => (None, payload),
+                Some(address) => {
+                    if payload.len() < LINK_HEADER_LEN {
+                        continue;
+                    }
+                    let header = LinkHeader {
+                        destination: payload[0],
+                        source: payload[1],
+                        control: payload[2],
+                        sequence: payload[3],
+                    };
+                    if header.destination != address && header.destination != BROADCAST_ADDRESS {
+                        continue;
+                    }
+                    (Some(header), &payload[LINK_HEADER_LEN..])
+                }
+            };
+
+            if payload.len() > N {
+                return Some(Err(Hc12Error::BufferFull));
+            }
+            self.payload_buffer[..payload.len()].copy_from_slice(payload);
+            return Some(Ok((header, payload.len())));
+        }
+
+        None
+    }
+
+    /// Handles a frame returned by `decode_buffered` for `recv_frame`. Returns the frame info once
+    /// the payload has been copied into `buffer`, and whether an acknowledgement must be sent.
+    /// Acknowledgements and duplicates of already delivered frames yield no frame info.
+    fn accept_frame(
+        &mut self,
+        header: Option<LinkHeader>,
+        len: usize,
+        buffer: &mut [u8],
+    ) -> Result<(Option<FrameInfo>, Option<LinkHeader>), Hc12Error> {
+        let Some(header) = header else {
+            self.link_stats.received += 1;
+            if len > buffer.len() {
+                return Err(Hc12Error::BufferFull);
+            }
+            buffer[..len].copy_from_slice(&self.payload_buffer[..len]);
+            return Ok((
+                Some(FrameInfo {
+                    len,
+                    source: None,
+                    destination: None,
+                }),
+                None,
+            ));
+        };
+
+        if header.control & CONTROL_ACK != 0 {
+            return Ok((None, None));
+        }
+
+        self.link_stats.received += 1;
+        let reliable = header.control & CONTROL_RELIABLE != 0;
+        let duplicate = header.control & (CONTROL_RELIABLE | CONTROL_SEQUENCED) != 0
+            && self.received_sequences.track(&header, &mut self.link_stats);
+        let ack = reliable.then_some(header);
+        if reliable && duplicate {
+            return Ok((None, ack));
+        }
+
+        if len > buffer.len() {
+            return Err(Hc12Error::BufferFull);
+        }
+        buffer[..len].copy_from_slice(&self.payload_buffer[..len]);
+
+        Ok((
+            Some(FrameInfo {
+                len,
+                source: Some(header.source),
+                destination: Some(header.destination),
+            }),
+            ack,
+        ))
+    }
+}
+
+#[cfg(feature = "esp32c3")]
+fn is_ack_for(header: Option<LinkHeader>, destination: u8, sequence: u8) -> bool {
+    header.is_some_and(|header| {
+        header.control & CONTROL_ACK != 0
+            && header.source == destination
+            && header.sequence == sequence
+    })
+}
+
+#[cfg(feature = "esp32c3")]
+impl<C: FrameCodec, const N: usize, D: DelayNs, S: OutputPin> Hc12Framed<'_, Blocking, C, N, D, S> {
+    fn transmit(&mut self, frame_len: usize) -> Result<(), Hc12Error> {
+        self.hc12.write_all(&self.tx_buffer[..frame_len])?;
+        self.hc12.flush()?;
+
+        if self.pacing {
+            let gap_ms = self.hc12.burst_gap_ms(frame_len);
+            self.hc12.delay.delay_ms(gap_ms);
+        }
+
+        Ok(())
+    }
+
+    fn fill_rx_buffer(&mut self) -> Result<(), Hc12Error> {
+        self.rx_start = 0;
+        self.rx_end = self.hc12.read_buffered(&mut self.rx_buffer)?;
+
+        Ok(())
+    }
+
+    /// Sends `payload` to all nodes.
+    pub fn send_frame(&mut self, payload: &[u8]) -> Result<(), Hc12Error> {
+        self.send_frame_to(BROADCAST_ADDRESS, payload)
+    }
+
+    /// Sends `payload` to the node with address `destination`. Without a local address the
+    /// destination is not transmitted and every node receives the frame.
+    pub fn send_frame_to(&mut self, destination: u8, payload: &[u8]) -> Result<(), Hc12Error> {
+        let (control, sequence) = self.next_header();
+        let frame_len = self.encode(destination, control, sequence, payload)?;
+
+        let jitter_ms = self.tx_jitter_ms();
+        self.hc12.delay.delay_ms(jitter_ms);
+        self.transmit(frame_len)
+    }
+
+    /// Sends `payload` to `destination` and waits for its acknowledgement, retransmitting
+    /// according to `policy`. Requires a local address and a unicast destination. Frames other
+    /// than the acknowledgement that arrive while waiting are dropped; reliable senders retransmit
+    /// them.
+    pub fn send_reliable(
+        &mut self,
+        destination: u8,
+        payload: &[u8],
+        policy: &ReliablePolicy,
+    ) -> Result<(), Hc12Error> {
+        let (control, sequence) = self.start_reliable(destination)?;
+
+        for attempt in 0..=policy.retries {
+            if attempt != 0 {
+                self.hc12.delay.delay_ms(policy.retry_spacing_ms);
+            }
+
+            let frame_len = self.encode(destination, control, sequence, payload)?;
+
+            let jitter_ms = self.tx_jitter_ms();
+            self.hc12.delay.delay_ms(jitter_ms);
+            self.transmit(frame_len)?;
+
+            let start = esp_hal::time::now();
+            while (esp_hal::time::now() - start).to_millis() < u64::from(policy.ack_timeout_ms) {
+                match self.decode_buffered() {
+                    Some(Ok((header, _))) if is_ack_for(header, destination, sequence) => {
+                        return Ok(());
+                    }
+                    Some(_) => {}
+                    None => self.fill_rx_buffer()?,
+                }
+            }
+        }
+
+        Err(Hc12Error::NoAck)
+    }
+
+    /// Blocks until a frame addressed to this node has been received and copies its payload into
+    /// `buffer`. Reliable frames are acknowledged and their retransmissions delivered only once.
+    /// Frames the codec rejects (e.g. [`Hc12Error::Crc`]) are reported as errors; the next call
+    /// continues with the following frame.
+    pub fn recv_frame(&mut self, buffer: &mut [u8]) -> Result<FrameInfo, Hc12Error> {
+        loop {
+            let Some(result) = self.decode_buffered() else {
+                self.fill_rx_buffer()?;
+                continue;
+            };
+            let (header, len) = result?;
+
+            let (frame_info, ack) = self.accept_frame(header, len, buffer)?;
+            if let Some(header) = ack {
+                let frame_len = self.encode(header.source, CONTROL_ACK, header.sequence, &[])?;
+                self.transmit(frame_len)?;
+            }
+            if let Some(frame_info) = frame_info {
+                return Ok(frame_info);
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "esp32c3", feature = "async"))]
+impl<C: FrameCodec, const N: usize, D, S: OutputPin> Hc12Framed<'_, Async, C, N, D, S> {
+    async fn transmit(&mut self, frame_len: usize) -> Result<(), Hc12Error> {
+        self.hc12
+            .write_all_async(&self.tx_buffer[..frame_len])
+            .await?;
+        self.hc12.flush_async().await?;
+
+        if self.pacing {
+            let gap_ms = self.hc12.burst_gap_ms(frame_len);
+            Timer::after_millis(u64::from(gap_ms)).await;
+        }
+
+        Ok(())
+    }
+
+    async fn fill_rx_buffer(&mut self) -> Result<(), Hc12Error> {
+        // Only touch the indices once the read completed, so a cancelled receive resumes with
+        // the bytes that were still buffered.
+        let bytes_read = self.hc12.read_async(&mut self.rx_buffer).await?;
+        self.rx_start = 0;
+        self.rx_end = bytes_read;
+
+        Ok(())
+    }
+
+    async fn wait_for_ack(&mut self, destination: u8, sequence: u8) -> Result<(), Hc12Error> {
+        loop {
+            match self.decode_buffered() {
+                Some(Ok((header, _))) if is_ack_for(header, destination, sequence) => {
+                    return Ok(());
+                }
+                Some(_) => {}
+                None => self.fill_rx_buffer().await?,
+            }
+        }
+    }
+
+    /// Sends `payload` to all nodes.
+    pub async fn send_frame(&mut self, payload: &[u8]) -> Result<(), Hc12Error> {
+        self.send_frame_to(BROADCAST_ADDRESS, payload).await
+    }
+
+    /// Sends `payload` to the node with address `destination`. Without a local address the
+    /// destination is not transmitted and every node receives the frame.
+    pub async fn send_frame_to(
+        &mut self,
+        destination: u8,
+        payload: &[u8],
+    ) -> Result<(), Hc12Error> {
+        let (control, sequence) = self.next_header();
+        let frame_len = self.encode(destination, control, sequence, payload)?;
+
+        Timer::after_millis(u64::from(self.tx_jitter_ms())).await;
+        self.transmit(frame_len).await
+    }
+
+    /// Sends `payload` to `destination` and waits for its acknowledgement, retransmitting
+    /// according to `policy`. Requires a local address and a unicast destination. Frames other
+    /// than the acknowledgement that arrive while waiting are dropped; reliable senders retransmit
+    /// them.
+    pub async fn send_reliable(
+        &mut self,
+        destination: u8,
+        payload: &[u8],
+        policy: &ReliablePolicy,
+    ) -> Result<(), Hc12Error> {
+        let (control, sequence) = self.start_reliable(destination)?;
+
+        for attempt in 0..=policy.retries {
+            if attempt != 0 {
+                Timer::after_millis(u64::from(policy.retry_spacing_ms)).await;
+            }
+
+            let frame_len = self.encode(destination, control, sequence, payload)?;
+
+            Timer::after_millis(u64::from(self.tx_jitter_ms())).await;
+            self.transmit(frame_len).await?;
+
+            let ack_timeout = Duration::from_millis(u64::from(policy.ack_timeout_ms));
+            if let Ok(result) =
+                with_timeout(ack_timeout, self.wait_for_ack(destination, sequence)).await
+            {
+                return result;
+            }
+        }
+
+        Err(Hc12Error::NoAck)
+    }
+
+    /// Waits until a frame addressed to this node has been received and copies its payload into
+    /// `buffer`. Reliable frames are acknowledged and their retransmissions delivered only once.
+    /// Frames the codec rejects (e.g. [`Hc12Error::Crc`]) are reported as errors; the next call
+    /// continues with the following frame.
+    pub async fn recv_frame(&mut self, buffer: &mut [u8]) -> Result<FrameInfo, Hc12Error> {
+        loop {
+            let Some(result) = self.decode_buffered() else {
+                self.fill_rx_buffer().await?;
+                continue;
+            };
+            let (header, len) = result?;
+
+            let (frame_info, ack) = self.accept_frame(header, len, buffer)?;
+            if let Some(header) = ack {
+                let frame_len = self.encode(header.source, CONTROL_ACK, header.sequence, &[])?;
+                self.transmit(frame_len).await?;
+            }
+            if let Some(frame_info) = frame_info {
+                return Ok(frame_info);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "postcard")]
+#[cfg(feature = "esp32c3")]
+impl<C: FrameCodec, const N: usize, D: DelayNs, S: OutputPin> Hc12Framed<'_, Blocking, C, N, D, S> {
+    /// Sends `message` postcard-encoded to all nodes. Messages that don't fit into a frame are
+    /// rejected with [`Hc12Error::FrameTooLarge`] before anything is transmitted.
+    pub fn send_msg<T: serde::Serialize>(&mut self, message: &T) -> Result<(), Hc12Error> {
+        self.send_msg_to(BROADCAST_ADDRESS, message)
+    }
+
+    /// Sends `message` postcard-encoded to the node with address `destination`.
+    pub fn send_msg_to<T: serde::Serialize>(
+        &mut self,
+        destination: u8,
+        message: &T,
+    ) -> Result<(), Hc12Error> {
+        let mut payload = [0u8; N];
+        let payload =
+            postcard::to_slice(message, &mut payload).map_err(|_| Hc12Error::FrameTooLarge)?;
+
+        self.send_frame_to(destination, payload)
+    }
+
+    /// Receives a frame into `buffer` and decodes its payload as a postcard-encoded `T`. Payloads
+    /// that don't decode fail with [`Hc12Error::Decode`].
+    pub fn recv_msg<T: serde::de::DeserializeOwned>(
+        &mut self,
+        buffer: &mut [u8],
+    ) -> Result<T, Hc12Error> {
+        let frame_info = self.recv_frame(buffer)?;
+
+        postcard::from_bytes(&buffer[..frame_info.len]).map_err(|_| Hc12Error::Decode)
+    }
+}
+
+#[cfg(feature = "postcard")]
+#[cfg(all(feature = "esp32c3", feature = "async"))]
+impl<C: FrameCodec, const N: usize, D, S: OutputPin> Hc12Framed<'_, Async, C, N, D, S> {
+    /// Sends `message` postcard-encoded to all nodes. Messages that don't fit into a frame are
+    /// rejected with [`Hc12Error::FrameTooLarge`] before anything is transmitted.
+    pub async fn send_msg<T: serde::Serialize>(&mut self, message: &T) -> Result<(), Hc12Error> {
+        self.send_msg_to(BROADCAST_ADDRESS, message).await
+    }
+
+    /// Sends `message` postcard-encoded to the node with address `destination`.
+    pub async fn send_msg_to<T: serde::Serialize>(
+        &mut self,
+        destination: u8,
+        message: &T,
+    ) -> Result<(), Hc12Error> {
+        let mut payload = [0u8; N];
+        let payload =
+            postcard::to_slice(message, &mut payload).map_err(|_| Hc12Error::FrameTooLarge)?;
+
+        self.send_frame_to(destination, payload).await
+    }
+
+    /// Receives a frame into `buffer` and decodes its payload as a postcard-encoded `T`. Payloads
+    /// that don't decode fail with [`Hc12Error::Decode`].
+    pub async fn recv_msg<T: serde::de::DeserializeOwned>(
+        &mut self,
+        buffer: &mut [u8],
+    ) -> Result<T, Hc12Error> {
+        let frame_info = self.recv_frame(buffer).await?;
+
+        postcard::from_bytes(&buffer[..frame_info.len]).map_err(|_| Hc12Error::Decode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes `payload` with [`cobs_encode`] and returns the encoding and its length.
+    fn encode(payload: &[u8]) -> ([u8; 600], usize) {
+        let mut encoded = [0u8; 600];
+        let len = cobs_encode(payload, &mut encoded).unwrap();
+        assert!(len <= cobs_max_encoded_len(payload.len()));
+        assert_eq!(encoded[len - 1], 0);
+        assert!(!encoded[..len - 1].contains(&0));
+
+        (encoded, len)
+    }
+
+    /// Feeds `chunks` into `codec` one after another and returns the decoded payloads.
+    fn decode_chunks<const N: usize>(codec: &mut CobsCodec<N>, chunks: &[&[u8]]) -> Vec<Vec<u8>> {
+        let mut frames = Vec::new();
+        for chunk in chunks {
+            for &byte in *chunk {
+                if let Some(result) = codec.decode(byte) {
+                    frames.push(result.unwrap().to_vec());
+                }
+            }
+        }
+
+        frames
+    }
+
+    fn round_trip<const N: usize>(payload: &[u8]) {
+        let (encoded, len) = encode(payload);
+        let mut codec = CobsCodec::<N>::new();
+
+        assert_eq!(
+            decode_chunks(&mut codec, &[&encoded[..len]]),
+            [payload.to_vec()]
+        );
+        assert_eq!(codec.discarded(), 0);
+    }
+
+    #[test]
+    fn cobs_round_trips_zero_bytes() {
+        round_trip::<16>(&[0]);
+        round_trip::<16>(&[0, 0, 0]);
+        round_trip::<16>(&[0, 1, 0]);
+        round_trip::<16>(&[1, 2, 0]);
+        round_trip::<16>(&[0, 1, 2]);
+    }
+
+    #[test]
+    fn cobs_encodes_zero_bytes_as_codes() {
+        let (encoded, len) = encode(&[0x11, 0x00, 0x00, 0x22]);
+
+        assert_eq!(&encoded[..len], [0x02, 0x11, 0x01, 0x02, 0x22, 0x00]);
+    }
+
+    #[test]
+    fn cobs_round_trips_block_boundary() {
+        for payload_len in [253, 254, 255, 508, 509] {
+            let payload: Vec<u8> = (0..payload_len).map(|i| (i % 255 + 1) as u8).collect();
+            round_trip::<512>(&payload);
+        }
+    }
+
+    #[test]
+    fn cobs_encodes_full_block_without_zero() {
+        let payload = [0x42; 254];
+        let (encoded, len) = encode(&payload);
+
+        assert_eq!(len, cobs_max_encoded_len(payload.len()));
+        assert_eq!(encoded[0], 0xFF);
+        assert_eq!(&encoded[1..255], payload);
+        assert_eq!(&encoded[255..len], [0x01, 0x00]);
+    }
+
+    #[test]
+    fn cobs_round_trips_zero_at_block_boundary() {
+        let mut payload = [0x42; 255];
+        payload[254] = 0;
+        round_trip::<255>(&payload);
+
+        let mut payload = [0x42; 256];
+        payload[253] = 0;
+        round_trip::<256>(&payload);
+    }
+
+    #[test]
+    fn cobs_round_trips_max_length_frame() {
+        let payload = [0x5A; 300];
+        round_trip::<300>(&payload);
+
+        let mut codec = CobsCodec::<300>::new();
+        let mut frame = [0u8; 600];
+        let len = codec.encode(&payload, &mut frame).unwrap();
+        assert_eq!(
+            decode_chunks(&mut codec, &[&frame[..len]]),
+            [payload.to_vec()]
+        );
+        assert_eq!(
+            codec.encode(&[0x5A; 301], &mut frame),
+            Err(Hc12Error::FrameTooLarge)
+        );
+    }
+
+    #[test]
+    fn cobs_codec_frame_fits_max_frame_len() {
+        let payload = [0x5A; 300];
+        let mut codec = CobsCodec::<300>::new();
+        let mut frame = [0u8; cobs_codec_max_frame_len(300)];
+
+        assert_eq!(codec.encode(&payload, &mut frame), Ok(frame.len()));
+        assert_eq!(
+            codec.encode(&payload, &mut frame[..cobs_max_encoded_len(300)]),
+            Err(Hc12Error::BufferFull)
+        );
+        assert_eq!(decode_chunks(&mut codec, &[&frame]), [payload.to_vec()]);
+    }
+
+    #[test]
+    fn cobs_drops_frame_longer_than_decoder() {
+        let (long, long_len) = encode(&[0x5A; 17]);
+        let (short, short_len) = encode(b"ok");
+        let mut codec = CobsCodec::<16>::new();
+
+        assert_eq!(
+            decode_chunks(&mut codec, &[&long[..long_len], &short[..short_len]]),
+            [b"ok".to_vec()]
+        );
+        assert_eq!(codec.discarded(), long_len as u32 - 1);
+    }
+
+    #[test]
+    fn cobs_decodes_frames_split_across_reads() {
+        let payload: Vec<u8> = (0..300).map(|i| (i % 7) as u8).collect();
+        let (encoded, len) = encode(&payload);
+
+        for split in [1, 2, 254, 255, 256, len - 1] {
+            let mut codec = CobsCodec::<300>::new();
+            let (first, second) = encoded[..len].split_at(split);
+
+            assert!(decode_chunks(&mut codec, &[first]).is_empty());
+            assert_eq!(decode_chunks(&mut codec, &[second]), [payload.as_slice()]);
+        }
+    }
+
+    #[test]
+    fn cobs_decodes_back_to_back_frames() {
+        let (first, first_len) = encode(&[1, 0, 2]);
+        let (second, second_len) = encode(&[]);
+        let (third, third_len) = encode(&[0; 4]);
+        let mut codec = CobsCodec::<16>::new();
+
+        assert_eq!(
+            decode_chunks(
+                &mut codec,
+                &[
+                    &first[..first_len],
+                    &second[..second_len],
+                    &third[..third_len]
+                ]
+            ),
+            [vec![1, 0, 2], vec![], vec![0; 4]]
+        );
+    }
+
+    fn header(source: u8, control: u8, sequence: u8) -> LinkHeader {
+        LinkHeader {
+            destination: 1,
+            source,
+            control,
+            sequence,
+        }
+    }
+
+    #[test]
+    fn sequence_history_drops_retransmissions() {
+        let mut history = SequenceHistory::new();
+        let mut link_stats = LinkStats::default();
+        let first = header(2, CONTROL_RELIABLE | CONTROL_RESTART, 1);
+        let second = header(2, CONTROL_RELIABLE, 2);
+
+        assert!(!history.track(&first, &mut link_stats));
+        assert!(history.track(&first, &mut link_stats));
+        assert!(!history.track(&second, &mut link_stats));
+        assert!(history.track(&second, &mut link_stats));
+    }
+
+    #[test]
+    fn sequence_history_accepts_first_frame_after_sender_reboot() {
+        let mut history = SequenceHistory::new();
+        let mut link_stats = LinkStats::default();
+
+        // The sender's last frame before the reboot has sequence 1, as does its first after it.
+        assert!(!history.track(
+            &header(2, CONTROL_RELIABLE | CONTROL_RESTART, 255),
+            &mut link_stats
+        ));
+        assert!(!history.track(&header(2, CONTROL_RELIABLE, 0), &mut link_stats));
+        assert!(!history.track(&header(2, CONTROL_RELIABLE, 1), &mut link_stats));
+
+        let rebooted = header(2, CONTROL_RELIABLE | CONTROL_RESTART, 1);
+        assert!(!history.track(&rebooted, &mut link_stats));
+        assert!(history.track(&rebooted, &mut link_stats));
+    }
+
+    #[test]
+    fn sequence_history_counts_no_loss_across_restart() {
+        let mut history = SequenceHistory::new();
+        let mut link_stats = LinkStats::default();
+
+        history.track(
+            &header(2, CONTROL_SEQUENCED | CONTROL_RESTART, 1),
+            &mut link_stats,
+        );
+        history.track(&header(2, CONTROL_SEQUENCED, 2), &mut link_stats);
+        history.track(&header(2, CONTROL_SEQUENCED, 5), &mut link_stats);
+        assert_eq!(link_stats.lost, 2);
+
+        history.track(
+            &header(2, CONTROL_SEQUENCED | CONTROL_RESTART, 9),
+            &mut link_stats,
+        );
+        assert_eq!(link_stats.lost, 2);
+        assert_eq!(link_stats.duplicated, 0);
+    }
+
+    #[test]
+    fn cobs_encode_rejects_short_output() {
+        let mut output = [0u8; 4];
+
+        assert_eq!(
+            cobs_encode(&[1, 2, 3], &mut output),
+            Err(Hc12Error::BufferFull)
+        );
+        assert_eq!(cobs_encode(&[1, 2], &mut output), Ok(4));
+    }
+}
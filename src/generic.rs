@@ -0,0 +1,567 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{OutputPin, PinState};
+use embedded_io::{Read, ReadReady, Write};
+
+use crate::command::{self, BaudSwitch, CommandTransport, RESPONSE_IDLE_MS};
+use crate::settings::drive_pin;
+use crate::{
+    BaudRate, Channel, Hc12Error, Hc12Params, SetPolarity, TransmissionMode, TransmitPower,
+};
+
+/// Serial port whose baud rate can be changed at runtime, which lets [`GenericHc12`] find the
+/// module with [`GenericHc12::auto_baud`] and follow it to a new baud rate.
+pub trait AdjustableBaud {
+    fn baud_rate(&self) -> u32;
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<(), Hc12Error>;
+}
+
+/// Command layer of the driver for any serial port implementing `embedded-io`, with any
+/// `embedded-hal` output pin for SET and any delay provider. It runs the same AT handling as
+/// [`Hc12`](crate::Hc12) and doesn't need esp-hal, so it is also built without the `esp32c3`
+/// feature and runs on hosts other than the ESP32.
+///
+/// Data goes straight through the port, reachable with [`GenericHc12::uart_mut`]. Unlike
+/// [`Hc12`](crate::Hc12), no state is tracked, and the host baud rate is only changed by the
+/// driver for ports implementing [`AdjustableBaud`]: after a successful
+/// [`GenericHc12::set_baud`], reconfigure the port to the new rate.
+pub struct GenericHc12<U, S, D> {
+    uart: U,
+    set: S,
+    set_polarity: SetPolarity,
+    delay: D,
+}
+
+impl<U, S, D> GenericHc12<U, S, D>
+where
+    U: Read + ReadReady + Write,
+    S: OutputPin,
+    D: DelayNs,
+{
+    /// Wraps a port connected to a powered-up module and drives SET to transparent mode.
+    pub fn new(
+        uart: U,
+        mut set: S,
+        set_polarity: SetPolarity,
+        delay: D,
+    ) -> Result<Self, Hc12Error> {
+        drive_pin(&mut set, set_polarity.transparent_level())?;
+
+        Ok(Self {
+            uart,
+            set,
+            set_polarity,
+            delay,
+        })
+    }
+
+    pub fn uart_mut(&mut self) -> &mut U {
+        &mut self.uart
+    }
+
+    /// Releases the port, the SET pin and the delay provider, with SET left in transparent mode.
+    pub fn into_parts(self) -> (U, S, D) {
+        (self.uart, self.set, self.delay)
+    }
+
+    pub fn test(&mut self) -> Result<(), Hc12Error> {
+        command::test(self)
+    }
+
+    pub fn set_baud(&mut self, baud_rate: &BaudRate) -> Result<(), Hc12Error> {
+        command::set_baud(self, baud_rate)
+    }
+
+    /// Returns the baud rate the module moved to, if the mode change forced one. Fails with
+    /// [`Hc12Error::InvalidResponse`] if that baud rate can't be parsed, although the module did
+    /// switch modes.
+    pub fn set_transmission_mode(
+        &mut self,
+        transmission_mode: &TransmissionMode,
+    ) -> Result<Option<BaudRate>, Hc12Error> {
+        command::set_transmission_mode(self, transmission_mode)?
+    }
+
+    pub fn set_channel(&mut self, channel: Channel) -> Result<(), Hc12Error> {
+        command::set_channel(self, channel)
+    }
+
+    pub fn set_transmit_power(&mut self, transmit_power: &TransmitPower) -> Result<(), Hc12Error> {
+        command::set_transmit_power(self, transmit_power)
+    }
+
+    /// Restores the factory settings, after which the module runs at 9600 baud.
+    pub fn set_default(&mut self) -> Result<(), Hc12Error> {
+        command::set_default(self)
+    }
+
+    pub fn get_channel(&mut self) -> Result<Channel, Hc12Error> {
+        command::get_channel(self)
+    }
+
+    pub fn get_params(&mut self) -> Result<Hc12Params, Hc12Error> {
+        command::get_params(self)
+    }
+
+    fn set_level(&mut self, level: PinState) -> Result<(), Hc12Error> {
+        drive_pin(&mut self.set, level)
+    }
+
+    fn enter_command_mode(&mut self) -> Result<(), Hc12Error> {
+        // Data still in the TX buffer would be taken for a command once SET switches.
+        self.uart.flush().map_err(|_| Hc12Error::Io)?;
+        let mut discard = [0u8; 16];
+        while self.uart.read_ready().map_err(|_| Hc12Error::Io)? {
+            self.uart.read(&mut discard).map_err(|_| Hc12Error::Io)?;
+        }
+
+        self.set_level(self.set_polarity.command_level())?;
+        self.delay.delay_ms(200);
+
+        Ok(())
+    }
+
+    fn exit_command_mode(&mut self) -> Result<(), Hc12Error> {
+        self.set_level(self.set_polarity.transparent_level())?;
+        self.delay.delay_ms(200);
+
+        Ok(())
+    }
+
+    fn exchange_in_command_mode(
+        &mut self,
+        command: &[u8],
+        response: &mut [u8],
+    ) -> Result<usize, Hc12Error> {
+        self.uart.write_all(command).map_err(|_| Hc12Error::Io)?;
+        self.uart.flush().map_err(|_| Hc12Error::Io)?;
+        self.delay.delay_ms(200);
+
+        let mut bytes_read = 0;
+        while bytes_read < response.len() && self.uart.read_ready().map_err(|_| Hc12Error::Io)? {
+            bytes_read += self
+                .uart
+                .read(&mut response[bytes_read..])
+                .map_err(|_| Hc12Error::Io)?;
+            self.delay.delay_ms(RESPONSE_IDLE_MS);
+        }
+
+        Ok(bytes_read)
+    }
+}
+
+impl<U, S, D> GenericHc12<U, S, D>
+where
+    U: Read + ReadReady + Write + AdjustableBaud,
+    S: OutputPin,
+    D: DelayNs,
+{
+    /// Finds the module's baud rate by probing every rate with `AT` and leaves the port at it.
+    /// Fails with [`Hc12Error::AutoBaudRate`] and what the probes saw if none answers.
+    pub fn auto_baud(&mut self) -> Result<BaudRate, Hc12Error> {
+        command::auto_baud(self)
+    }
+
+    /// Changes baud rate and channel under a single SET toggle and moves the port to the new
+    /// baud rate. If the channel is rejected the baud change is undone before leaving command
+    /// mode, so module and port stay on the current baud rate.
+    pub fn set_baud_and_channel(
+        &mut self,
+        baud_rate: &BaudRate,
+        channel: Channel,
+    ) -> Result<(), Hc12Error> {
+        let previous_baud_rate = BaudRate::try_from(self.uart.baud_rate())?;
+
+        let result = {
+            let mut command_mode = GenericCommandMode::new(self)?;
+            command::set_baud_and_channel(
+                &mut command_mode,
+                baud_rate,
+                channel,
+                &previous_baud_rate,
+            )
+        };
+
+        match result {
+            Ok(()) => self.uart.set_baud_rate(u32::from(baud_rate)),
+            Err((error, true)) => Err(error),
+            Err((error, false)) => {
+                // The module is on the new baud rate now, follow it.
+                self.uart.set_baud_rate(u32::from(baud_rate))?;
+                Err(error)
+            }
+        }
+    }
+}
+
+impl<U, S, D> CommandTransport for GenericHc12<U, S, D>
+where
+    U: Read + ReadReady + Write,
+    S: OutputPin,
+    D: DelayNs,
+{
+    fn exchange(&mut self, command: &[u8], response: &mut [u8]) -> Result<usize, Hc12Error> {
+        self.enter_command_mode()?;
+        let result = self.exchange_in_command_mode(command, response);
+        self.exit_command_mode()?;
+
+        result
+    }
+}
+
+impl<U, S, D> BaudSwitch for GenericHc12<U, S, D>
+where
+    U: Read + ReadReady + Write + AdjustableBaud,
+    S: OutputPin,
+    D: DelayNs,
+{
+    fn switch_host_baud(&mut self, baud_rate: BaudRate) -> Result<(), Hc12Error> {
+        self.uart.flush().map_err(|_| Hc12Error::Io)?;
+        self.uart.set_baud_rate(u32::from(baud_rate))
+    }
+
+    fn wait_ms(&mut self, ms: u32) {
+        self.delay.delay_ms(ms);
+    }
+}
+
+/// Keeps the module of a [`GenericHc12`] in command mode while alive, so several commands share
+/// one SET toggle.
+struct GenericCommandMode<'a, U, S, D>
+where
+    U: Read + ReadReady + Write,
+    S: OutputPin,
+    D: DelayNs,
+{
+    hc12: &'a mut GenericHc12<U, S, D>,
+}
+
+impl<'a, U, S, D> GenericCommandMode<'a, U, S, D>
+where
+    U: Read + ReadReady + Write,
+    S: OutputPin,
+    D: DelayNs,
+{
+    fn new(hc12: &'a mut GenericHc12<U, S, D>) -> Result<Self, Hc12Error> {
+        hc12.enter_command_mode()?;
+
+        Ok(Self { hc12 })
+    }
+}
+
+impl<U, S, D> CommandTransport for GenericCommandMode<'_, U, S, D>
+where
+    U: Read + ReadReady + Write,
+    S: OutputPin,
+    D: DelayNs,
+{
+    fn exchange(&mut self, command: &[u8], response: &mut [u8]) -> Result<usize, Hc12Error> {
+        self.hc12.exchange_in_command_mode(command, response)
+    }
+}
+
+impl<U, S, D> Drop for GenericCommandMode<'_, U, S, D>
+where
+    U: Read + ReadReady + Write,
+    S: OutputPin,
+    D: DelayNs,
+{
+    fn drop(&mut self) {
+        let _ = self.hc12.exit_command_mode();
+    }
+}
+
+/// Serial port for [`GenericHc12`] replaying canned responses, for exercising timeouts, retries
+/// and response parsing on the host. Each flush following a write makes the next response
+/// readable, handed out in chunks of `chunk_len` bytes with `gap_polls` unready
+/// [`ReadReady::read_ready`] polls between them, so slow or split responses can be scripted.
+/// Written bytes are recorded up to `W` bytes, so [`MockUart::written`] shows the exact command
+/// bytes each [`GenericHc12`] method puts on the wire. The port starts at 9600 baud and follows
+/// [`AdjustableBaud::set_baud_rate`], without affecting the responses.
+#[cfg(any(test, feature = "mock"))]
+pub struct MockUart<'a, const W: usize> {
+    responses: &'a [&'a [u8]],
+    next_response: usize,
+    pending: &'a [u8],
+    chunk_len: usize,
+    chunk_left: usize,
+    gap_polls: u32,
+    polls_left: u32,
+    unanswered: bool,
+    written: heapless::Vec<u8, W>,
+    baud_rate: u32,
+}
+
+#[cfg(any(test, feature = "mock"))]
+impl<'a, const W: usize> MockUart<'a, W> {
+    pub fn new(responses: &'a [&'a [u8]]) -> Self {
+        Self {
+            responses,
+            next_response: 0,
+            pending: &[],
+            chunk_len: usize::MAX,
+            chunk_left: usize::MAX,
+            gap_polls: 0,
+            polls_left: 0,
+            unanswered: false,
+            written: heapless::Vec::new(),
+            baud_rate: 9600,
+        }
+    }
+
+    /// Splits every response into chunks of `chunk_len` bytes, separated by `gap_polls` polls
+    /// reporting no data.
+    pub fn with_chunking(mut self, chunk_len: usize, gap_polls: u32) -> Self {
+        self.chunk_len = chunk_len.max(1);
+        self.chunk_left = self.chunk_len;
+        self.gap_polls = gap_polls;
+        self
+    }
+
+    /// The first `W` bytes written to the port.
+    pub fn written(&self) -> &[u8] {
+        &self.written
+    }
+}
+
+#[cfg(any(test, feature = "mock"))]
+impl<const W: usize> AdjustableBaud for MockUart<'_, W> {
+    fn baud_rate(&self) -> u32 {
+        self.baud_rate
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<(), Hc12Error> {
+        self.baud_rate = baud_rate;
+
+        Ok(())
+    }
+}
+
+#[cfg(any(test, feature = "mock"))]
+impl<const W: usize> embedded_io::ErrorType for MockUart<'_, W> {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(any(test, feature = "mock"))]
+impl<const W: usize> Read for MockUart<'_, W> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let len = buf.len().min(self.pending.len()).min(self.chunk_left);
+        buf[..len].copy_from_slice(&self.pending[..len]);
+        self.pending = &self.pending[len..];
+        self.chunk_left -= len;
+
+        if self.chunk_left == 0 {
+            self.chunk_left = self.chunk_len;
+            self.polls_left = self.gap_polls;
+        }
+
+        Ok(len)
+    }
+}
+
+#[cfg(any(test, feature = "mock"))]
+impl<const W: usize> ReadReady for MockUart<'_, W> {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        if self.pending.is_empty() {
+            return Ok(false);
+        }
+        if self.polls_left > 0 {
+            self.polls_left -= 1;
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(any(test, feature = "mock"))]
+impl<const W: usize> Write for MockUart<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let len = buf.len().min(W - self.written.len());
+        let _ = self.written.extend_from_slice(&buf[..len]);
+        self.unanswered |= !buf.is_empty();
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        if core::mem::take(&mut self.unanswered) {
+            self.pending = self
+                .responses
+                .get(self.next_response)
+                .copied()
+                .unwrap_or(&[]);
+            self.next_response += 1;
+            self.chunk_left = self.chunk_len;
+            self.polls_left = 0;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use super::*;
+    use crate::AutoBaudFailure;
+
+    struct NoPin;
+
+    impl embedded_hal::digital::ErrorType for NoPin {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for NoPin {
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    struct NoDelay;
+
+    impl DelayNs for NoDelay {
+        fn delay_ns(&mut self, _ns: u32) {}
+    }
+
+    fn hc12<'a>(uart: MockUart<'a, 64>) -> GenericHc12<MockUart<'a, 64>, NoPin, NoDelay> {
+        GenericHc12::new(uart, NoPin, SetPolarity::ActiveLow, NoDelay).unwrap()
+    }
+
+    #[test]
+    fn auto_baud_follows_the_rate_that_answers() {
+        let mut hc12 = hc12(MockUart::new(&[b"", b"", b"", b"OK\r\n"]));
+
+        assert_eq!(hc12.auto_baud(), Ok(BaudRate::Baud9600));
+        assert_eq!(hc12.uart_mut().baud_rate(), 9600);
+        assert_eq!(hc12.uart_mut().written(), b"ATATATAT");
+    }
+
+    #[test]
+    fn auto_baud_reads_split_response() {
+        let mut hc12 = hc12(MockUart::new(&[b"OK\r\n"]).with_chunking(1, 0));
+
+        assert_eq!(hc12.auto_baud(), Ok(BaudRate::Baud1200));
+    }
+
+    #[test]
+    fn auto_baud_reports_silent_module() {
+        let mut hc12 = hc12(MockUart::new(&[]));
+
+        assert_eq!(
+            hc12.auto_baud(),
+            Err(Hc12Error::AutoBaudRate(
+                AutoBaudFailure::NoResponseAtAnyBaud
+            ))
+        );
+        assert_eq!(hc12.uart_mut().baud_rate(), 115200);
+    }
+
+    #[test]
+    fn auto_baud_reports_garbage() {
+        let mut hc12 = hc12(MockUart::new(&[b"\xf0\x0f", b"", b"\x80"]));
+
+        assert_eq!(
+            hc12.auto_baud(),
+            Err(Hc12Error::AutoBaudRate(AutoBaudFailure::GarbageAtAllBauds))
+        );
+    }
+
+    #[test]
+    fn auto_baud_reports_partial_response() {
+        let mut hc12 = hc12(MockUart::new(&[b"\xf0", b"OK+\r\n", b"ERROR\r\n"]));
+
+        assert_eq!(
+            hc12.auto_baud(),
+            Err(Hc12Error::AutoBaudRate(AutoBaudFailure::PartialResponseAt(
+                BaudRate::Baud2400
+            )))
+        );
+    }
+
+    #[test]
+    fn auto_baud_gives_up_on_response_cut_by_a_gap() {
+        let mut hc12 = hc12(MockUart::new(&[b"OK\r\n"]).with_chunking(1, 1));
+
+        assert_eq!(
+            hc12.auto_baud(),
+            Err(Hc12Error::AutoBaudRate(AutoBaudFailure::GarbageAtAllBauds))
+        );
+    }
+
+    #[test]
+    fn set_baud_and_channel_follows_module() {
+        let mut hc12 = hc12(MockUart::new(&[b"OK+B19200\r\n", b"OK+C042\r\n"]));
+
+        assert_eq!(
+            hc12.set_baud_and_channel(&BaudRate::Baud19200, Channel::new(42).unwrap()),
+            Ok(())
+        );
+        assert_eq!(hc12.uart_mut().baud_rate(), 19200);
+        assert_eq!(hc12.uart_mut().written(), b"AT+B19200AT+C042");
+    }
+
+    #[test]
+    fn set_baud_and_channel_rolls_back_rejected_channel() {
+        let mut hc12 = hc12(MockUart::new(&[
+            b"OK+B19200\r\n",
+            b"ERROR\r\n",
+            b"OK+B9600\r\n",
+        ]));
+
+        assert!(matches!(
+            hc12.set_baud_and_channel(&BaudRate::Baud19200, Channel::new(42).unwrap()),
+            Err(Hc12Error::ModuleRejected(_))
+        ));
+        assert_eq!(hc12.uart_mut().baud_rate(), 9600);
+        assert_eq!(hc12.uart_mut().written(), b"AT+B19200AT+C042AT+B9600");
+    }
+
+    #[test]
+    fn set_baud_and_channel_follows_module_if_rollback_fails() {
+        let mut hc12 = hc12(MockUart::new(&[b"OK+B19200\r\n", b"ERROR\r\n", b""]));
+
+        assert!(hc12
+            .set_baud_and_channel(&BaudRate::Baud19200, Channel::new(42).unwrap())
+            .is_err());
+        assert_eq!(hc12.uart_mut().baud_rate(), 19200);
+    }
+
+    #[test]
+    fn set_baud_and_channel_keeps_rate_if_baud_rejected() {
+        let mut hc12 = hc12(MockUart::new(&[b"OK+B4800\r\n"]));
+
+        assert_eq!(
+            hc12.set_baud_and_channel(&BaudRate::Baud19200, Channel::new(42).unwrap()),
+            Err(Hc12Error::BaudRate)
+        );
+        assert_eq!(hc12.uart_mut().baud_rate(), 9600);
+        assert_eq!(hc12.uart_mut().written(), b"AT+B19200");
+    }
+
+    #[test]
+    fn rejects_malformed_responses() {
+        let mut hc12 = hc12(MockUart::new(&[
+            b"OK+B96\r\n",
+            b"OK+C1x7\r\n",
+            b"OK+FU4,1200\r\n",
+            b"OK+RC\r\n",
+        ]));
+
+        assert_eq!(hc12.set_baud(&BaudRate::Baud9600), Err(Hc12Error::BaudRate));
+        assert_eq!(
+            hc12.set_channel(Channel::new(107).unwrap()),
+            Err(Hc12Error::Channel)
+        );
+        assert_eq!(
+            hc12.set_transmission_mode(&TransmissionMode::Fu4),
+            Err(Hc12Error::InvalidResponse)
+        );
+        assert_eq!(hc12.get_channel(), Err(Hc12Error::InvalidResponse));
+    }
+}
@@ -1,447 +1,2666 @@
-use core::fmt::Write;
 use embassy_embedded_hal::SetConfig;
-use embassy_time::Timer;
+#[cfg(feature = "async")]
+use embassy_time::{with_deadline, Duration, Instant, Timer};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{OutputPin, PinState};
 use esp_hal::{
-    gpio::OutputOpenDrain,
+    delay::Delay,
+    gpio::{
+        interconnect::{InputSignal, OutputSignal},
+        Level, Output, OutputOpenDrain, Pin, Pull,
+    },
     peripheral::Peripheral,
-    uart::{Config, Uart},
-    Async, Blocking, DriverMode,
+    uart::{AtCmdConfig, Config, Uart},
+    Blocking, DriverMode,
 };
-use heapless::{String, Vec};
+#[cfg(feature = "async")]
+use esp_hal::{interrupt::Priority, peripherals::Interrupt, uart::Instance, Async};
+use heapless::Vec;
+
+use crate::command::{self, BaudSwitch, CommandTransport, PARAMS_RESPONSE_LEN, RESPONSE_IDLE_MS};
+use crate::poll::PendingCommand;
+#[cfg(feature = "async")]
+use crate::protocol::{self, COMMAND_LEN};
+use crate::ring_buffer::RingBuffer;
+use crate::settings::drive_pin;
+#[cfg(feature = "async")]
+use crate::AutoBaudFailure;
+use crate::{
+    BaudRate, Channel, DutyCycle, Hc12Error, Hc12Params, LineTerminator, PowerLevel, SetPolarity,
+    TransmissionMode, TransmitPower,
+};
+
+/// How a SET pin set up by [`Hc12::from_uart_with_set_drive`] is driven.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SetDrive {
+    /// Open drain with the given internal pull. The other constructors use `Pull::None` and rely
+    /// on the module's own pull-up.
+    OpenDrain(Pull),
+    /// Push-pull, for SET lines too long or too loaded for the pull-up to raise them in time.
+    PushPull,
+}
+
+impl Default for SetDrive {
+    fn default() -> Self {
+        SetDrive::OpenDrain(Pull::None)
+    }
+}
+
+/// A GPIO SET pin in either drive mode, see [`SetDrive`].
+pub enum SetPin<'d> {
+    OpenDrain(OutputOpenDrain<'d>),
+    PushPull(Output<'d>),
+}
+
+impl<'d> SetPin<'d> {
+    pub fn new(
+        pin: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        drive: SetDrive,
+        initial_level: PinState,
+    ) -> Self {
+        let initial_level = level(initial_level);
+        match drive {
+            SetDrive::OpenDrain(pull) => {
+                SetPin::OpenDrain(OutputOpenDrain::new(pin, initial_level, pull))
+            }
+            SetDrive::PushPull => SetPin::PushPull(Output::new(pin, initial_level)),
+        }
+    }
+}
+
+impl embedded_hal::digital::ErrorType for SetPin<'_> {
+    type Error = core::convert::Infallible;
+}
+
+impl OutputPin for SetPin<'_> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        match self {
+            SetPin::OpenDrain(pin) => pin.set_low(),
+            SetPin::PushPull(pin) => pin.set_low(),
+        }
+
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        match self {
+            SetPin::OpenDrain(pin) => pin.set_high(),
+            SetPin::PushPull(pin) => pin.set_high(),
+        }
+
+        Ok(())
+    }
+}
+
+/// Inversion of the UART lines at the GPIO matrix, for boards with an inverting level shifter or
+/// opto-coupler between host and module. AT commands and `auto_baud` work the same on inverted
+/// lines.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct UartInversion {
+    pub rx_invert: bool,
+    pub tx_invert: bool,
+}
+
+/// How a UART set up by the driver is wired, as far as the driver needs to know it later.
+#[derive(Clone, Copy)]
+pub(crate) struct UartWiring {
+    /// GPIO number of the RX line.
+    rx_pin: u8,
+    /// The UART's interrupt.
+    #[cfg(feature = "async")]
+    interrupt: Interrupt,
+}
+
+/// Sets up `uart` with `config` on `rx` and `tx`, inverting the lines `inversion` selects. Also
+/// returns the GPIO number of `rx` and the interrupt of `uart`.
+pub(crate) fn connect_uart<'d>(
+    uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+    rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+    tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+    config: Config,
+    inversion: UartInversion,
+) -> Result<(Uart<'d, Blocking>, UartWiring), Hc12Error> {
+    let uart = uart.into_ref();
+    #[cfg(feature = "async")]
+    let interrupt = uart.info().interrupt;
+    let rx = rx.into_ref();
+    let rx_pin = rx.number();
+    let mut rx = rx.map_into::<InputSignal>();
+    if inversion.rx_invert {
+        rx.invert();
+    }
+    let mut tx = tx.into_ref().map_into::<OutputSignal>();
+    if inversion.tx_invert {
+        tx.invert();
+    }
+
+    let uart = Uart::new(uart, config)
+        .map_err(|_| Hc12Error::Config)?
+        .with_rx(rx)
+        .with_tx(tx);
+
+    let wiring = UartWiring {
+        rx_pin,
+        #[cfg(feature = "async")]
+        interrupt,
+    };
+
+    Ok((uart, wiring))
+}
+
+/// esp-hal's counterpart of the SET level `state`.
+fn level(state: PinState) -> Level {
+    Level::from(bool::from(state))
+}
+
+/// SET pin of an [`Hc12`] and its polarity. It remembers whether it holds the module in command
+/// mode and, when dropped in that state, drives SET back to transparent mode.
+pub(crate) struct SetLine<S: OutputPin> {
+    pin: Option<S>,
+    pub(crate) polarity: SetPolarity,
+    pub(crate) command_mode: bool,
+}
+
+impl<S: OutputPin> SetLine<S> {
+    pub(crate) fn new(pin: Option<S>, polarity: SetPolarity) -> Self {
+        Self {
+            pin,
+            polarity,
+            command_mode: false,
+        }
+    }
+
+    /// Drives SET to `level`, if there is a pin.
+    pub(crate) fn drive(&mut self, level: PinState) -> Result<(), Hc12Error> {
+        let Some(pin) = self.pin.as_mut() else {
+            return Ok(());
+        };
+        // Set first: after a failed write the level is unknown, so the drop still tries to exit.
+        self.command_mode = level == self.polarity.command_level();
+
+        drive_pin(pin, level)
+    }
+
+    pub(crate) fn is_none(&self) -> bool {
+        self.pin.is_none()
+    }
+
+    pub(crate) fn take(&mut self) -> Option<S> {
+        self.command_mode = false;
+        self.pin.take()
+    }
+}
+
+impl<S: OutputPin> Drop for SetLine<S> {
+    fn drop(&mut self) {
+        if self.command_mode {
+            let _ = self.drive(self.polarity.transparent_level());
+        }
+    }
+}
+
+/// Waits of the command-mode handling that can be tuned with [`Hc12::set_timings`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hc12Timings {
+    /// Wait after SET returns to transparent mode, before data or the next command. The module
+    /// needs about 200 ms to leave command mode and, after `AT+B`, to switch baud rates. A
+    /// sequence of setting commands that doesn't change the baud rate can run with less, as long
+    /// as the last one before data goes out is followed by the full wait.
+    pub post_command_settle_ms: u32,
+    /// Time the RX drain before a command may take before giving up with [`Hc12Error::Busy`] on a
+    /// channel that doesn't go quiet, e.g. while the peer is transmitting.
+    pub drain_timeout_ms: u32,
+}
+
+impl Default for Hc12Timings {
+    fn default() -> Self {
+        Self {
+            post_command_settle_ms: 200,
+            drain_timeout_ms: 100,
+        }
+    }
+}
+
+/// Module configuration applied by [`Hc12::bring_up`]. The default matches the factory settings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hc12Config {
+    pub baud_rate: BaudRate,
+    pub channel: Channel,
+    pub transmission_mode: TransmissionMode,
+    pub transmit_power: TransmitPower,
+}
+
+impl Default for Hc12Config {
+    fn default() -> Self {
+        Self {
+            baud_rate: BaudRate::default(),
+            channel: Channel::MIN,
+            transmission_mode: TransmissionMode::default(),
+            transmit_power: TransmitPower::default(),
+        }
+    }
+}
+
+impl Hc12Config {
+    /// Checks that the module can run this configuration: channel and baud rate must be supported
+    /// in the transmission mode, otherwise [`Hc12Error::IncompatibleMode`] is returned.
+    pub fn validate(&self) -> Result<(), Hc12Error> {
+        if !self
+            .transmission_mode
+            .channels()
+            .contains(&self.channel.get())
+            || !self.transmission_mode.supports_baud_rate(&self.baud_rate)
+        {
+            return Err(Hc12Error::IncompatibleMode);
+        }
+
+        Ok(())
+    }
+}
+
+/// The configuration the driver believes the module is in, updated by every setter. The default
+/// holds the factory settings.
+///
+/// The state is only tracked, never read back, so resets or changes outside the driver (e.g. a
+/// power cycle of the module) make it go stale. Re-applying the configuration with
+/// [`Hc12::bring_up`] brings both back in line, [`Hc12::resync`] adopts the module's settings.
+/// [`Hc12::reconfigure`] relies on it to skip the commands that wouldn't change anything.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hc12State {
+    pub baud_rate: BaudRate,
+    pub channel: Channel,
+    pub transmission_mode: TransmissionMode,
+    pub transmit_power: TransmitPower,
+    pub asleep: bool,
+}
+
+impl Default for Hc12State {
+    fn default() -> Self {
+        Self {
+            baud_rate: BaudRate::default(),
+            channel: Channel::MIN,
+            transmission_mode: TransmissionMode::default(),
+            transmit_power: TransmitPower::default(),
+            asleep: false,
+        }
+    }
+}
+
+/// HC-12 driver. The blocking API waits through `D`, which defaults to esp-hal's [`Delay`] and can
+/// be replaced with [`Hc12::new_with_delay`]. SET is driven through `S`, an open-drain GPIO unless
+/// another `embedded-hal` output is passed to [`Hc12::from_parts`].
+///
+/// Dropping the driver while it holds the module in command mode, e.g. when an error or a
+/// cancelled future leaves a command unfinished, drives SET back to transparent mode. Drop can't
+/// wait, so the module only accepts data again about 200 ms later; the constructors that run the
+/// power-on sequence cover that. The UART is dropped with the driver: TX and RX stay routed to
+/// the peripheral as esp-hal leaves them, and the module just sees an idle line.
+pub struct Hc12<'d, Dm: esp_hal::DriverMode, D = Delay, S: OutputPin = OutputOpenDrain<'d>> {
+    pub(crate) uart: Uart<'d, Dm>,
+    wiring: Option<UartWiring>,
+    uart_config: Config,
+    pub(crate) set: SetLine<S>,
+    pub(crate) delay: D,
+    line_terminator: LineTerminator,
+    pub(crate) state: Hc12State,
+    duty_cycle: Option<DutyCycle>,
+    pub(crate) last_response: Vec<u8, PARAMS_RESPONSE_LEN>,
+    pub(crate) pending_command: Option<PendingCommand>,
+    pub(crate) rx_buffer: RingBuffer<RX_BUFFER_LEN>,
+    pub(crate) last_rx_ms: Option<u64>,
+    min_rx_idle_ms: u32,
+    delimiter: Option<u8>,
+    timings: Hc12Timings,
+    /// Whether [`Hc12::state`] was established as a whole, by applying or reading all settings,
+    /// rather than assumed.
+    state_known: bool,
+}
+
+/// Time the module takes to switch to a new baud rate after leaving command mode, waited after the
+/// host UART follows it.
+pub(crate) const BAUD_SWITCH_SETTLE_MS: u32 = 20;
+
+/// Size of the UART's TX and RX FIFOs.
+const UART_FIFO_LEN: usize = 128;
+
+/// Size of the driver's RX buffer, which holds bytes taken from the UART FIFO until they are read,
+/// matching the size of the FIFO itself. It bounds how far [`Hc12::peek`] can look ahead and the
+/// length of lines [`Hc12::receive_line`] can return in async mode.
+pub const RX_BUFFER_LEN: usize = UART_FIFO_LEN;
+
+/// Time since the last received byte after which `try_send` considers the channel free, unless
+/// changed with [`Hc12::set_min_rx_idle_ms`].
+pub const DEFAULT_MIN_RX_IDLE_MS: u32 = 50;
+
+/// Delay before retry `attempt + 1`: `base_delay_ms` doubled for every previous retry.
+fn backoff_delay_ms(base_delay_ms: u32, attempt: u8) -> u32 {
+    base_delay_ms.saturating_mul(1u32.checked_shl(u32::from(attempt)).unwrap_or(u32::MAX))
+}
+
+impl<Dm: DriverMode, D, S: OutputPin> Hc12<'_, Dm, D, S> {
+    /// Same as [`Hc12::try_read`].
+    pub fn read_buffered(&mut self, buffer: &mut [u8]) -> Result<usize, esp_hal::uart::Error> {
+        self.try_read(buffer)
+    }
+
+    /// Reads whatever has been received so far into `buffer` without waiting, first from the RX
+    /// buffer and then from the UART FIFO.
+    pub fn try_read(&mut self, buffer: &mut [u8]) -> Result<usize, esp_hal::uart::Error> {
+        let buffered = self.rx_buffer.pop_into(buffer);
+        if buffered == buffer.len() {
+            return Ok(buffered);
+        }
+
+        let bytes_read = self.uart.read_buffered_bytes(&mut buffer[buffered..])?;
+        self.note_rx(bytes_read);
+
+        Ok(buffered + bytes_read)
+    }
+
+    /// The byte `n` positions ahead in the RX buffer, without consuming anything. Only bytes
+    /// already moved into the buffer, e.g. by [`Hc12::rx_available`] or [`Hc12::wait_for_data`],
+    /// can be peeked at.
+    pub fn peek(&self, n: usize) -> Option<u8> {
+        self.rx_buffer.get(n)
+    }
+
+    /// Drops up to `n` bytes from the RX buffer and returns how many were dropped.
+    pub fn skip(&mut self, n: usize) -> usize {
+        self.rx_buffer.discard(n)
+    }
+
+    /// Number of received bytes ready to be read without waiting. Bytes waiting in the UART FIFO
+    /// are moved into the RX buffer to count them, so at most [`RX_BUFFER_LEN`] are reported.
+    pub fn rx_available(&mut self) -> Result<usize, esp_hal::uart::Error> {
+        let mut buffer = [0u8; RX_BUFFER_LEN];
+        let free = self.rx_buffer.free();
+        let bytes_read = self.uart.read_buffered_bytes(&mut buffer[..free])?;
+        self.note_rx(bytes_read);
+        self.rx_buffer.push_slice(&buffer[..bytes_read]);
+
+        Ok(self.rx_buffer.len())
+    }
+
+    /// Whether at least one received byte can be read without waiting.
+    pub fn data_available(&mut self) -> Result<bool, esp_hal::uart::Error> {
+        Ok(self.rx_available()? != 0)
+    }
+
+    pub fn line_terminator(&self) -> LineTerminator {
+        self.line_terminator
+    }
+
+    pub fn set_line_terminator(&mut self, line_terminator: LineTerminator) {
+        self.line_terminator = line_terminator;
+    }
+
+    pub fn set_polarity(&self) -> SetPolarity {
+        self.set.polarity
+    }
+
+    pub fn state(&self) -> &Hc12State {
+        &self.state
+    }
+
+    /// The transmission mode last applied through this driver.
+    pub fn transmission_mode(&self) -> TransmissionMode {
+        self.state.transmission_mode
+    }
+
+    /// Raw bytes of the last command response, including its line ending, for debugging.
+    pub fn last_response(&self) -> &[u8] {
+        &self.last_response
+    }
+
+    /// The baud rate the host UART is currently configured for.
+    pub fn baud_rate(&self) -> BaudRate {
+        self.state.baud_rate
+    }
+
+    /// Time in milliseconds the module needs to send `bytes` over the air at the current mode and
+    /// baud rate, rounded up.
+    pub fn air_time_ms(&self, bytes: usize) -> u32 {
+        self.state
+            .transmission_mode
+            .air_time_ms(&self.state.baud_rate, bytes)
+    }
+
+    /// Bytes that can be written in one burst at the current mode and baud rate without overrunning
+    /// the module, see [`TransmissionMode::max_chunk`].
+    pub fn max_chunk_bytes(&self) -> usize {
+        self.state
+            .transmission_mode
+            .max_chunk(&self.state.baud_rate)
+    }
+
+    /// Time since the last received byte `try_send` waits for before transmitting.
+    pub fn min_rx_idle_ms(&self) -> u32 {
+        self.min_rx_idle_ms
+    }
+
+    pub fn set_min_rx_idle_ms(&mut self, min_rx_idle_ms: u32) {
+        self.min_rx_idle_ms = min_rx_idle_ms;
+    }
+
+    /// Records the arrival time of data read from the UART, if any arrived.
+    fn note_rx(&mut self, bytes_read: usize) {
+        if bytes_read != 0 {
+            self.last_rx_ms = Some(esp_hal::time::now().duration_since_epoch().to_millis());
+        }
+    }
+
+    /// Drives SET to `level`, if there is a SET pin. Paths into command mode check for one with
+    /// [`Hc12::require_set_pin`] first.
+    pub(crate) fn drive_set(&mut self, level: PinState) -> Result<(), Hc12Error> {
+        self.set.drive(level)
+    }
+
+    pub(crate) fn require_set_pin(&self) -> Result<(), Hc12Error> {
+        if self.set.is_none() {
+            return Err(Hc12Error::NoSetPin);
+        }
+
+        Ok(())
+    }
+
+    /// Whether the peer may be transmitting: received bytes are waiting or the last one arrived
+    /// less than [`Hc12::min_rx_idle_ms`] ago.
+    fn channel_busy(&mut self) -> Result<bool, Hc12Error> {
+        if self.rx_available()? != 0 {
+            return Ok(true);
+        }
+
+        let now_ms = esp_hal::time::now().duration_since_epoch().to_millis();
+        Ok(self
+            .last_rx_ms
+            .is_some_and(|last_rx_ms| now_ms - last_rx_ms < u64::from(self.min_rx_idle_ms)))
+    }
+
+    pub fn duty_cycle(&self) -> Option<&DutyCycle> {
+        self.duty_cycle.as_ref()
+    }
+
+    /// Enables the duty-cycle governor, or disables it with `None`. It covers `write_all`,
+    /// `write_paced`, `send_chunked`, `send_line` and the framed layers, but not the raw `write`
+    /// methods.
+    pub fn set_duty_cycle(&mut self, duty_cycle: Option<DutyCycle>) {
+        self.duty_cycle = duty_cycle;
+    }
+
+    /// Air time in milliseconds the duty-cycle governor currently allows, if enabled.
+    pub fn duty_cycle_remaining_ms(&mut self) -> Option<u32> {
+        let now_ms = esp_hal::time::now().duration_since_epoch().to_millis();
+
+        self.duty_cycle
+            .as_mut()
+            .map(|duty_cycle| duty_cycle.remaining_ms(now_ms))
+    }
+
+    /// Charges the air time of `bytes` to the duty-cycle budget. On failure returns how long to
+    /// wait until the budget allows the transmission, or `None` if it never will.
+    fn reserve_air_time(&mut self, bytes: usize) -> Result<(), Option<u32>> {
+        let air_time_ms = self.air_time_ms(bytes);
+        let now_ms = esp_hal::time::now().duration_since_epoch().to_millis();
+
+        match self.duty_cycle.as_mut() {
+            Some(duty_cycle) => duty_cycle.try_consume(air_time_ms, now_ms),
+            None => Ok(()),
+        }
+    }
+
+    /// Pause in milliseconds after a burst of `bytes` before the next one may follow in the
+    /// current mode.
+    pub(crate) fn burst_gap_ms(&self, bytes: usize) -> u32 {
+        self.state
+            .transmission_mode
+            .burst_gap_ms(&self.state.baud_rate, bytes)
+    }
+
+    pub(crate) fn set_host_baud(
+        &mut self,
+        baud_rate: BaudRate,
+    ) -> Result<(), esp_hal::uart::ConfigError> {
+        self.uart
+            .set_config(&self.uart_config.with_baudrate(u32::from(baud_rate)))?;
+        self.state.baud_rate = baud_rate;
+        trace!("host baud rate switched to {}", u32::from(baud_rate));
+
+        Ok(())
+    }
+
+    /// Applies `uart_config` at the current baud rate and keeps it for later baud rate changes.
+    fn update_uart_config(&mut self, uart_config: Config) -> Result<(), Hc12Error> {
+        let uart_config = uart_config.with_baudrate(u32::from(self.state.baud_rate));
+        self.uart
+            .set_config(&uart_config)
+            .map_err(|_| Hc12Error::Config)?;
+        self.uart_config = uart_config;
+
+        Ok(())
+    }
+
+    pub fn rx_fifo_full_threshold(&self) -> u16 {
+        self.uart_config.rx_fifo_full_threshold
+    }
+
+    /// Number of bytes in the RX FIFO that wakes up an async read. A low threshold gets bytes to
+    /// the frame readers sooner at high baud rates; a high one, for slow links and battery nodes,
+    /// wakes the CPU less often and leaves short messages to the RX timeout. Kept across baud
+    /// rate changes.
+    pub fn set_rx_fifo_full_threshold(&mut self, threshold: u16) -> Result<(), Hc12Error> {
+        self.update_uart_config(self.uart_config.with_rx_fifo_full_threshold(threshold))
+    }
+
+    pub fn timings(&self) -> Hc12Timings {
+        self.timings
+    }
+
+    pub fn set_timings(&mut self, timings: Hc12Timings) {
+        self.timings = timings;
+    }
+
+    pub fn delimiter(&self) -> Option<u8> {
+        self.delimiter
+    }
+
+    /// Makes the UART raise its character-detect (AT_CMD) interrupt when `delimiter` is received,
+    /// e.g. `0x00` for COBS frames or `b'\n'` for lines. Async reads then wake as soon as a frame
+    /// is complete instead of at the FIFO threshold or RX timeout; see
+    /// [`Hc12::wait_for_delimiter`]. The detection can't be switched off again, only moved to
+    /// another character.
+    pub fn set_delimiter(&mut self, delimiter: u8) {
+        self.uart
+            .set_at_cmd(AtCmdConfig::new(None, None, None, delimiter, Some(1)));
+        self.delimiter = Some(delimiter);
+    }
+
+    pub fn rx_timeout(&self) -> Option<u8> {
+        self.uart_config.rx_timeout
+    }
+
+    /// Idle time on RX, in character times, after which an async read wakes up with what arrived
+    /// below the FIFO threshold, `None` to only wake on the threshold. About two characters end a
+    /// read right after a burst, which is when a frame is complete; since the timeout counts
+    /// characters, it scales with the baud rate on its own. Kept across baud rate changes.
+    pub fn set_rx_timeout(&mut self, rx_timeout: Option<u8>) -> Result<(), Hc12Error> {
+        self.update_uart_config(self.uart_config.with_rx_timeout(rx_timeout))
+    }
+
+    /// Tracks a confirmed transmission mode change and follows the module to `new_baud_rate` if
+    /// the change forced one. If the forced baud rate couldn't be parsed the mode is still
+    /// tracked, the host UART left alone and the parse error returned.
+    pub(crate) fn apply_transmission_mode(
+        &mut self,
+        transmission_mode: &TransmissionMode,
+        new_baud_rate: Result<Option<BaudRate>, Hc12Error>,
+    ) -> Result<(), Hc12Error> {
+        self.state.transmission_mode = *transmission_mode;
+        if let Some(new_baud_rate) = new_baud_rate? {
+            self.set_host_baud(new_baud_rate)
+                .map_err(|_| Hc12Error::TransmissionMode)?;
+        }
+
+        Ok(())
+    }
+
+    /// Follows the module after `set_baud_and_channel`. `result` carries the error and whether the
+    /// baud change could be undone if the channel was rejected.
+    fn finish_baud_and_channel(
+        &mut self,
+        baud_rate: &BaudRate,
+        channel: Channel,
+        result: Result<(), (Hc12Error, bool)>,
+    ) -> Result<(), Hc12Error> {
+        match result {
+            Ok(()) => {
+                self.state.channel = channel;
+                self.set_host_baud(*baud_rate)
+                    .map_err(|_| Hc12Error::BaudRate)
+            }
+            Err((error, true)) => Err(error),
+            Err((error, false)) => {
+                // The module is on the new baud rate now, follow it.
+                self.set_host_baud(*baud_rate)
+                    .map_err(|_| Hc12Error::BaudRate)?;
+                Err(error)
+            }
+        }
+    }
+
+    /// Replaces the state with the settings read from the module, which is awake as it just
+    /// answered.
+    fn adopt_params(&mut self, params: &Hc12Params) -> Hc12State {
+        self.state.channel = params.channel;
+        self.state.transmission_mode = params.transmission_mode;
+        self.state.transmit_power = params.transmit_power;
+        self.state.asleep = false;
+        self.state_known = true;
+
+        self.state
+    }
+
+    /// Resets the state to the factory settings the module returns to after `AT+DEFAULT` and
+    /// follows it to 9600 baud.
+    pub(crate) fn reset_state(&mut self) -> Result<(), Hc12Error> {
+        self.set_host_baud(BaudRate::default())
+            .map_err(|_| Hc12Error::Default)?;
+        self.state = Hc12State::default();
+        self.state_known = true;
+
+        Ok(())
+    }
+
+    /// Keeps `response` for [`Hc12::last_response`], truncated to its capacity.
+    pub(crate) fn store_response(&mut self, response: &[u8]) {
+        let len = response.len().min(PARAMS_RESPONSE_LEN);
+        self.last_response = Vec::from_slice(&response[..len]).unwrap();
+    }
+
+    /// Discards the RX buffer and what the UART FIFO holds right now, without waiting for the
+    /// channel to go quiet. A single read empties the FIFO, so this never blocks.
+    pub(crate) fn discard_rx(&mut self) {
+        self.rx_buffer.clear();
+
+        let mut buffer = [0u8; UART_FIFO_LEN];
+        let _ = self.uart.read_buffered_bytes(&mut buffer);
+    }
+
+    /// Discards everything received so far, buffered or still in the UART FIFO. Fails with
+    /// [`Hc12Error::Busy`] if bytes keep arriving for [`Hc12Timings::drain_timeout_ms`], e.g. while
+    /// the peer is transmitting.
+    pub(crate) fn drain_rx(&mut self) -> Result<(), Hc12Error> {
+        self.rx_buffer.clear();
+
+        let start = esp_hal::time::now();
+        let mut buffer = [0u8; 14];
+        while self
+            .uart
+            .read_buffered_bytes(&mut buffer)
+            .is_ok_and(|bytes_read: usize| bytes_read != 0)
+        {
+            if (esp_hal::time::now() - start).to_millis()
+                >= u64::from(self.timings.drain_timeout_ms)
+            {
+                return Err(Hc12Error::Busy);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn validate_channel(&self, channel: Channel) -> Result<(), Hc12Error> {
+        if !self
+            .state
+            .transmission_mode
+            .channels()
+            .contains(&channel.get())
+        {
+            return Err(Hc12Error::IncompatibleMode);
+        }
+
+        Ok(())
+    }
+}
+
+impl<'d, Dm: DriverMode, D, S: OutputPin> Hc12<'d, Dm, D, S> {
+    /// Wraps a UART running with `uart_config` and a SET pin. Fails if the UART runs at a baud
+    /// rate the module doesn't support.
+    fn assemble(
+        uart: Uart<'d, Dm>,
+        wiring: Option<UartWiring>,
+        uart_config: Config,
+        set: Option<S>,
+        set_polarity: SetPolarity,
+        delay: D,
+    ) -> Result<Self, Hc12Error> {
+        let baud_rate = BaudRate::try_from(uart_config.baudrate).map_err(|_| Hc12Error::Config)?;
+
+        Ok(Self {
+            uart,
+            wiring,
+            uart_config,
+            set: SetLine::new(set, set_polarity),
+            delay,
+            line_terminator: LineTerminator::default(),
+            state: Hc12State {
+                baud_rate,
+                ..Hc12State::default()
+            },
+            duty_cycle: None,
+            last_response: Vec::new(),
+            pending_command: None,
+            rx_buffer: RingBuffer::new(),
+            last_rx_ms: None,
+            min_rx_idle_ms: DEFAULT_MIN_RX_IDLE_MS,
+            delimiter: None,
+            timings: Hc12Timings::default(),
+            state_known: false,
+        })
+    }
+
+    pub fn uart(&self) -> &Uart<'d, Dm> {
+        &self.uart
+    }
+
+    /// The UART, for settings the driver doesn't cover, e.g. flow control. Changing the baud rate
+    /// this way leaves [`Hc12::state`] behind and breaks commands until `auto_baud` resyncs; use
+    /// [`Hc12::set_baud`] instead. Config set here is lost on the next baud rate change; use
+    /// [`Hc12::set_rx_fifo_full_threshold`] and [`Hc12::set_rx_timeout`] for the RX knobs.
+    pub fn uart_mut(&mut self) -> &mut Uart<'d, Dm> {
+        &mut self.uart
+    }
+
+    /// GPIO number of the RX line, `None` if the UART was set up outside the driver. To wake from
+    /// light sleep when the peer starts sending, enable this GPIO as a low-level wakeup source:
+    /// RX idles high and the first start bit pulls it low.
+    pub fn rx_pin(&self) -> Option<u8> {
+        self.wiring.map(|wiring| wiring.rx_pin)
+    }
+
+    /// Releases the UART and the SET pin, e.g. to talk to the module at a baud rate the driver
+    /// doesn't support or to lend the pins to another driver. SET is left in transparent mode, so
+    /// the UART can be used right away. Bytes in the RX buffer and a command started with one of
+    /// the `poll_*` methods are dropped. The pin is `None` for a driver built with
+    /// [`Hc12::from_uart_transparent`].
+    pub fn into_parts(mut self) -> (Uart<'d, Dm>, Option<S>) {
+        let _ = self.drive_set(self.set.polarity.transparent_level());
+
+        (self.uart, self.set.take())
+    }
+}
+
+impl<'d, Dm: DriverMode, S: OutputPin> Hc12<'d, Dm, Delay, S> {
+    /// Wraps a UART and SET pin released by [`Hc12::into_parts`], or set up by hand, without the
+    /// power-on sequence. SET can be any `embedded-hal` output, e.g. a pin of an I/O expander, and
+    /// is driven to transparent mode right away; pin errors are reported as [`Hc12Error::Io`]. The
+    /// state starts out as for a UART running with `Config::default()`; if the UART runs at
+    /// another baud rate, use `auto_baud` to find the module again.
+    pub fn from_parts(
+        uart: Uart<'d, Dm>,
+        mut set: S,
+        set_polarity: SetPolarity,
+    ) -> Result<Self, Hc12Error> {
+        drive_pin(&mut set, set_polarity.transparent_level())?;
+
+        // `Config::default()` runs at 115200 baud, which the module supports.
+        Self::assemble(
+            uart,
+            None,
+            Config::default(),
+            Some(set),
+            set_polarity,
+            Delay::new(),
+        )
+    }
+}
+
+impl<'d, Dm: DriverMode> Hc12<'d, Dm> {
+    /// Wraps a UART set up elsewhere, e.g. with pins or settings the constructors don't cover, and
+    /// sets up only the SET pin, driven to transparent mode right away. As with
+    /// [`Hc12::from_parts`], the state assumes `Config::default()`.
+    pub fn from_uart(
+        uart: Uart<'d, Dm>,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+    ) -> Result<Self, Hc12Error> {
+        Self::from_uart_with_set_polarity(uart, set, SetPolarity::default())
+    }
+
+    /// Like [`Hc12::from_uart`], for boards that invert the SET line.
+    pub fn from_uart_with_set_polarity(
+        uart: Uart<'d, Dm>,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set_polarity: SetPolarity,
+    ) -> Result<Self, Hc12Error> {
+        let set = OutputOpenDrain::new(
+            set,
+            level(set_polarity.transparent_level()),
+            esp_hal::gpio::Pull::None,
+        );
+
+        Self::from_parts(uart, set, set_polarity)
+    }
+
+    /// Wraps a UART connected to a module whose SET line is strapped to transparent mode, with
+    /// `Config::default()` assumed as for [`Hc12::from_parts`]. Reading and writing data work as
+    /// usual; everything that needs command mode fails with [`Hc12Error::NoSetPin`].
+    pub fn from_uart_transparent(uart: Uart<'d, Dm>) -> Result<Self, Hc12Error> {
+        Self::assemble(
+            uart,
+            None,
+            Config::default(),
+            None,
+            SetPolarity::default(),
+            Delay::new(),
+        )
+    }
+}
+
+impl<'d, Dm: DriverMode> Hc12<'d, Dm, Delay, SetPin<'d>> {
+    /// Like [`Hc12::from_uart_with_set_polarity`], with the SET pin driven as `drive` selects
+    /// instead of open drain without pull.
+    pub fn from_uart_with_set_drive(
+        uart: Uart<'d, Dm>,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set_polarity: SetPolarity,
+        drive: SetDrive,
+    ) -> Result<Self, Hc12Error> {
+        let set = SetPin::new(set, drive, set_polarity.transparent_level());
+
+        Self::from_parts(uart, set, set_polarity)
+    }
+}
+
+impl<'d> Hc12<'d, Blocking> {
+    /// Sets up `uart` on `rx` and `tx` with `Config::default()` and runs the power-on SET
+    /// sequence, which leaves the module in transparent mode, ready for data. Up to version 0.1.5
+    /// the sequence left SET in command mode instead.
+    pub fn new(
+        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+    ) -> Result<Self, Hc12Error> {
+        Self::new_with_delay(uart, rx, tx, set, Delay::new())
+    }
+
+    /// Like [`Hc12::new`], but sets the UART up with `config` instead of `Config::default()`, e.g.
+    /// for a different RX FIFO threshold. Changing the baud rate later keeps everything else of
+    /// `config`. Fails with [`Hc12Error::Config`] if `config` uses a baud rate the module doesn't
+    /// support.
+    pub fn new_with_config(
+        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        config: Config,
+    ) -> Result<Self, Hc12Error> {
+        let (uart, wiring) = connect_uart(uart, rx, tx, config, UartInversion::default())?;
+        Self::init(
+            uart,
+            Some(wiring),
+            set,
+            config,
+            Delay::new(),
+            SetPolarity::default(),
+        )
+    }
+
+    /// Like [`Hc12::new`], for boards that invert the RX or TX line, e.g. through an opto-coupler.
+    /// The lines are inverted at the GPIO matrix, so the UART sees the levels the module sends.
+    pub fn new_with_inversion(
+        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        inversion: UartInversion,
+    ) -> Result<Self, Hc12Error> {
+        let (uart, wiring) = connect_uart(uart, rx, tx, Config::default(), inversion)?;
+        Self::init(
+            uart,
+            Some(wiring),
+            set,
+            Config::default(),
+            Delay::new(),
+            SetPolarity::default(),
+        )
+    }
+
+    /// Like [`Hc12::new`], for boards that invert the SET line.
+    pub fn new_with_set_polarity(
+        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set_polarity: SetPolarity,
+    ) -> Result<Self, Hc12Error> {
+        Self::new_with_delay_and_set_polarity(uart, rx, tx, set, Delay::new(), set_polarity)
+    }
+
+    /// Like [`Hc12::new_with_set_polarity`], but for a module that is already powered up and
+    /// initialized, e.g. after a warm reset of the host. SET is driven to transparent mode right
+    /// away, without the power-on sequence and its waits.
+    pub fn new_without_init(
+        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set_polarity: SetPolarity,
+    ) -> Result<Self, Hc12Error> {
+        let (uart, wiring) =
+            connect_uart(uart, rx, tx, Config::default(), UartInversion::default())?;
+        let set = OutputOpenDrain::new(
+            set,
+            level(set_polarity.transparent_level()),
+            esp_hal::gpio::Pull::None,
+        );
+
+        Self::assemble(
+            uart,
+            Some(wiring),
+            Config::default(),
+            Some(set),
+            set_polarity,
+            Delay::new(),
+        )
+    }
+
+    /// Like [`Hc12::new_without_init`], but with the UART running at `baud_rate` right away, for a
+    /// module whose settings are known, e.g. from the previous boot. Nothing is checked and
+    /// nothing waited for; if the module runs at another baud rate, commands fail until
+    /// `auto_baud` finds it.
+    pub fn new_unchecked(
+        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        baud_rate: BaudRate,
+    ) -> Result<Self, Hc12Error> {
+        let config = Config::default().with_baudrate(u32::from(baud_rate));
+        let (uart, wiring) = connect_uart(uart, rx, tx, config, UartInversion::default())?;
+        let set = OutputOpenDrain::new(
+            set,
+            level(SetPolarity::default().transparent_level()),
+            esp_hal::gpio::Pull::None,
+        );
+
+        Self::assemble(
+            uart,
+            Some(wiring),
+            config,
+            Some(set),
+            SetPolarity::default(),
+            Delay::new(),
+        )
+    }
+}
+
+impl<'d, D: DelayNs> Hc12<'d, Blocking, D> {
+    /// Like [`Hc12::new`], but all waits of the blocking API go through `delay`.
+    pub fn new_with_delay(
+        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        delay: D,
+    ) -> Result<Self, Hc12Error> {
+        Self::new_with_delay_and_set_polarity(uart, rx, tx, set, delay, SetPolarity::default())
+    }
+
+    /// Combines [`Hc12::new_with_delay`] and [`Hc12::new_with_set_polarity`].
+    pub fn new_with_delay_and_set_polarity(
+        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        delay: D,
+        set_polarity: SetPolarity,
+    ) -> Result<Self, Hc12Error> {
+        let (uart, wiring) =
+            connect_uart(uart, rx, tx, Config::default(), UartInversion::default())?;
+        Self::init(
+            uart,
+            Some(wiring),
+            set,
+            Config::default(),
+            delay,
+            set_polarity,
+        )
+    }
+
+    /// Runs the power-on sequence on `set` for a UART connected with `config`.
+    pub(crate) fn init(
+        uart: Uart<'d, Blocking>,
+        wiring: Option<UartWiring>,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        config: Config,
+        mut delay: D,
+        set_polarity: SetPolarity,
+    ) -> Result<Self, Hc12Error> {
+        let mut set = OutputOpenDrain::new(
+            set,
+            level(set_polarity.command_level()),
+            esp_hal::gpio::Pull::None,
+        );
+        Self::power_on(&mut set, set_polarity, &mut delay)?;
+
+        Self::assemble(uart, wiring, config, Some(set), set_polarity, delay)
+    }
+}
+
+impl<'d, S: OutputPin> Hc12<'d, Blocking, Delay, S> {
+    /// Like [`Hc12::from_parts`], but runs the power-on SET sequence of [`Hc12::new`], for a
+    /// module powered up together with the host.
+    pub fn from_parts_with_init(
+        uart: Uart<'d, Blocking>,
+        mut set: S,
+        set_polarity: SetPolarity,
+    ) -> Result<Self, Hc12Error> {
+        let mut delay = Delay::new();
+        Self::power_on(&mut set, set_polarity, &mut delay)?;
+
+        // `Config::default()` runs at 115200 baud, which the module supports.
+        Self::assemble(
+            uart,
+            None,
+            Config::default(),
+            Some(set),
+            set_polarity,
+            delay,
+        )
+    }
+}
+
+impl<'d, D: DelayNs, S: OutputPin> Hc12<'d, Blocking, D, S> {
+    /// The SET sequence run on power-up: inactive, active, inactive again, 200 ms each. It ends
+    /// in transparent mode, like the async `init`, as the driver starts out assuming the module
+    /// isn't in command mode. Up to version 0.1.5 it stopped after the active step and left the
+    /// module in command mode until the first command.
+    fn power_on(set: &mut S, set_polarity: SetPolarity, delay: &mut D) -> Result<(), Hc12Error> {
+        drive_pin(set, set_polarity.transparent_level())?;
+        delay.delay_ms(200);
+        drive_pin(set, set_polarity.command_level())?;
+        delay.delay_ms(200);
+        drive_pin(set, set_polarity.transparent_level())?;
+        delay.delay_ms(200);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'d, D, S: OutputPin> Hc12<'d, Blocking, D, S> {
+    /// Converts the driver into async mode without re-running the power-on SET sequence. The
+    /// tracked state, the duty-cycle budget and bytes in the RX buffer carry over. A command
+    /// started with one of the `poll_*` methods is abandoned and the module returned to
+    /// transparent mode.
+    pub fn into_async(mut self) -> Hc12<'d, Async, D, S> {
+        if self.pending_command.take().is_some() {
+            let _ = self.drive_set(self.set.polarity.transparent_level());
+        }
+
+        Hc12 {
+            uart: self.uart.into_async(),
+            wiring: self.wiring,
+            uart_config: self.uart_config,
+            set: self.set,
+            delay: self.delay,
+            line_terminator: self.line_terminator,
+            state: self.state,
+            duty_cycle: self.duty_cycle,
+            last_response: self.last_response,
+            pending_command: None,
+            rx_buffer: self.rx_buffer,
+            last_rx_ms: self.last_rx_ms,
+            min_rx_idle_ms: self.min_rx_idle_ms,
+            delimiter: self.delimiter,
+            timings: self.timings,
+            state_known: self.state_known,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'d> Hc12<'d, Async> {
+    pub async fn new(
+        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+    ) -> Result<Self, Hc12Error> {
+        Self::new_with_set_polarity(uart, rx, tx, set, SetPolarity::default()).await
+    }
+
+    /// Like [`Hc12::new`], but sets the UART up with `config` instead of `Config::default()`, e.g.
+    /// for a different RX FIFO threshold. Changing the baud rate later keeps everything else of
+    /// `config`. Fails with [`Hc12Error::Config`] if `config` uses a baud rate the module doesn't
+    /// support.
+    pub async fn new_with_config(
+        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        config: Config,
+    ) -> Result<Self, Hc12Error> {
+        let (uart, wiring) = connect_uart(uart, rx, tx, config, UartInversion::default())?;
+        Self::init(uart, Some(wiring), set, config, SetPolarity::default()).await
+    }
+
+    /// Like [`Hc12::new`], for boards that invert the RX or TX line, e.g. through an opto-coupler.
+    /// The lines are inverted at the GPIO matrix, so the UART sees the levels the module sends.
+    pub async fn new_with_inversion(
+        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        inversion: UartInversion,
+    ) -> Result<Self, Hc12Error> {
+        let (uart, wiring) = connect_uart(uart, rx, tx, Config::default(), inversion)?;
+        Self::init(
+            uart,
+            Some(wiring),
+            set,
+            Config::default(),
+            SetPolarity::default(),
+        )
+        .await
+    }
+
+    /// Like [`Hc12::new`], for boards that invert the SET line.
+    pub async fn new_with_set_polarity(
+        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set_polarity: SetPolarity,
+    ) -> Result<Self, Hc12Error> {
+        let (uart, wiring) =
+            connect_uart(uart, rx, tx, Config::default(), UartInversion::default())?;
+        Self::init(uart, Some(wiring), set, Config::default(), set_polarity).await
+    }
+
+    /// Runs the power-on sequence on `set` for a UART connected with `config`.
+    pub(crate) async fn init(
+        uart: Uart<'d, Blocking>,
+        wiring: Option<UartWiring>,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        config: Config,
+        set_polarity: SetPolarity,
+    ) -> Result<Self, Hc12Error> {
+        let uart = uart.into_async();
+
+        let mut set = OutputOpenDrain::new(
+            set,
+            level(set_polarity.command_level()),
+            esp_hal::gpio::Pull::None,
+        );
+        set.set_level(level(set_polarity.transparent_level()));
+        Timer::after_millis(200).await;
+
+        Self::assemble(uart, wiring, config, Some(set), set_polarity, Delay::new())
+    }
+
+    /// Like [`Hc12::new_with_set_polarity`], but for a module that is already powered up and
+    /// initialized, e.g. after a warm reset of the host. SET is driven to transparent mode right
+    /// away, without the power-on sequence and its waits.
+    pub fn new_without_init(
+        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set_polarity: SetPolarity,
+    ) -> Result<Self, Hc12Error> {
+        Ok(Hc12::<'d, Blocking>::new_without_init(uart, rx, tx, set, set_polarity)?.into_async())
+    }
+
+    /// Like [`Hc12::new_without_init`], but with the UART running at `baud_rate` right away, for a
+    /// module whose settings are known, e.g. from the previous boot. Nothing is checked and
+    /// nothing waited for; if the module runs at another baud rate, commands fail until
+    /// `auto_baud` finds it.
+    pub fn new_unchecked(
+        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
+        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
+        baud_rate: BaudRate,
+    ) -> Result<Self, Hc12Error> {
+        Ok(Hc12::<'d, Blocking>::new_unchecked(uart, rx, tx, set, baud_rate)?.into_async())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'d, S: OutputPin> Hc12<'d, Async, Delay, S> {
+    /// Like [`Hc12::from_parts`], but runs the power-on SET sequence of [`Hc12::new`], for a
+    /// module powered up together with the host.
+    pub async fn from_parts_with_init(
+        uart: Uart<'d, Async>,
+        mut set: S,
+        set_polarity: SetPolarity,
+    ) -> Result<Self, Hc12Error> {
+        drive_pin(&mut set, set_polarity.transparent_level())?;
+        Timer::after_millis(200).await;
+
+        Self::from_parts(uart, set, set_polarity)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'d, D, S: OutputPin> Hc12<'d, Async, D, S> {
+    /// Converts the driver into blocking mode without re-running the power-on SET sequence. The
+    /// tracked state, the duty-cycle budget and bytes in the RX buffer carry over.
+    pub fn into_blocking(self) -> Hc12<'d, Blocking, D, S> {
+        Hc12 {
+            uart: self.uart.into_blocking(),
+            wiring: self.wiring,
+            uart_config: self.uart_config,
+            set: self.set,
+            delay: self.delay,
+            line_terminator: self.line_terminator,
+            state: self.state,
+            duty_cycle: self.duty_cycle,
+            last_response: self.last_response,
+            pending_command: self.pending_command,
+            rx_buffer: self.rx_buffer,
+            last_rx_ms: self.last_rx_ms,
+            min_rx_idle_ms: self.min_rx_idle_ms,
+            delimiter: self.delimiter,
+            timings: self.timings,
+            state_known: self.state_known,
+        }
+    }
+}
+
+/// Keeps the module in command mode while alive so several commands share one SET toggle. It
+/// returns the module to transparent mode when dropped, also on early returns.
+pub(crate) struct CommandModeGuard<'a, 'd, D: DelayNs, S: OutputPin> {
+    hc12: &'a mut Hc12<'d, Blocking, D, S>,
+}
+
+impl<'a, 'd, D: DelayNs, S: OutputPin> CommandModeGuard<'a, 'd, D, S> {
+    pub(crate) fn new(hc12: &'a mut Hc12<'d, Blocking, D, S>) -> Result<Self, Hc12Error> {
+        hc12.enter_command_mode()?;
+
+        Ok(Self { hc12 })
+    }
+}
+
+impl<D: DelayNs, S: OutputPin> CommandTransport for CommandModeGuard<'_, '_, D, S> {
+    fn exchange(&mut self, command: &[u8], response: &mut [u8]) -> Result<usize, Hc12Error> {
+        trace!("sending command {:?}", command);
+        self.hc12.uart.write_bytes(command)?;
+        self.hc12.delay.delay_ms(200);
+
+        let mut bytes_read = 0;
+        loop {
+            let chunk = self
+                .hc12
+                .uart
+                .read_buffered_bytes(&mut response[bytes_read..])?;
+            bytes_read += chunk;
+            if chunk == 0 || bytes_read == response.len() {
+                break;
+            }
+            self.hc12.delay.delay_ms(RESPONSE_IDLE_MS);
+        }
+        trace!("received response {:?}", &response[..bytes_read]);
+        self.hc12.store_response(&response[..bytes_read]);
+
+        Ok(bytes_read)
+    }
+}
+
+impl<D: DelayNs, S: OutputPin> Drop for CommandModeGuard<'_, '_, D, S> {
+    fn drop(&mut self) {
+        let _ = self.hc12.exit_command_mode();
+    }
+}
+
+impl<D: DelayNs, S: OutputPin> CommandTransport for Hc12<'_, Blocking, D, S> {
+    fn exchange(&mut self, command: &[u8], response: &mut [u8]) -> Result<usize, Hc12Error> {
+        CommandModeGuard::new(self)?.exchange(command, response)
+    }
+}
+
+impl<D: DelayNs, S: OutputPin> BaudSwitch for Hc12<'_, Blocking, D, S> {
+    fn switch_host_baud(&mut self, baud_rate: BaudRate) -> Result<(), Hc12Error> {
+        self.flush()?;
+        self.set_host_baud(baud_rate).map_err(|_| Hc12Error::Config)
+    }
+
+    fn wait_ms(&mut self, ms: u32) {
+        self.delay.delay_ms(ms);
+    }
+}
+
+impl<D: DelayNs, S: OutputPin> Hc12<'_, Blocking, D, S> {
+    /// Switches the module into command mode, after sending what is still queued and discarding
+    /// what was received, so AT commands can be written and their responses read with the plain
+    /// I/O methods. Nothing sent in command mode reaches the peer, and the built-in commands
+    /// toggle SET on their own, so call [`Hc12::exit_command_mode`] before using either.
+    pub fn enter_command_mode(&mut self) -> Result<(), Hc12Error> {
+        self.require_no_pending_command()?;
+        self.require_set_pin()?;
+        // Data still in the TX FIFO would be taken for a command once SET switches.
+        self.flush()?;
+        self.drain_rx()?;
+
+        // Command mode also wakes a sleeping module.
+        self.drive_set(self.set.polarity.command_level())?;
+        self.state.asleep = false;
+        self.delay.delay_ms(200);
+
+        Ok(())
+    }
+
+    /// Returns the module to transparent mode after [`Hc12::enter_command_mode`] and waits
+    /// [`Hc12Timings::post_command_settle_ms`].
+    pub fn exit_command_mode(&mut self) -> Result<(), Hc12Error> {
+        self.drive_set(self.set.polarity.transparent_level())?;
+        self.delay.delay_ms(self.timings.post_command_settle_ms);
+
+        Ok(())
+    }
+
+    pub fn test(&mut self) -> Result<(), Hc12Error> {
+        command::test(self)
+    }
+
+    /// Finds the module's baud rate by probing every rate with `AT` and follows it. Fails with
+    /// [`Hc12Error::AutoBaudRate`] if none answers, telling a silent line from garbage or a
+    /// garbled answer, and logs which it was; [`PoweredHc12::recover`](crate::PoweredHc12::recover)
+    /// can rescue a module that answers garbage.
+    pub fn auto_baud(&mut self) -> Result<BaudRate, Hc12Error> {
+        command::auto_baud(self)
+    }
+
+    pub fn set_baud(&mut self, baud_rate: &BaudRate) -> Result<(), Hc12Error> {
+        command::set_baud(self, baud_rate)?;
+
+        // Bytes still in the TX FIFO would go out at the new rate once the UART is reconfigured.
+        self.flush()?;
+        self.set_host_baud(*baud_rate)
+            .map_err(|_| Hc12Error::TransmissionMode)?;
+        self.delay.delay_ms(BAUD_SWITCH_SETTLE_MS);
+
+        Ok(())
+    }
+
+    /// Switches the transmission mode and follows the module to the baud rate the mode forces,
+    /// e.g. 1200 baud for FU4. If the module's report of that baud rate can't be parsed, the mode
+    /// change is still tracked but the host UART stays put and [`Hc12Error::InvalidResponse`] is
+    /// returned; use `auto_baud` to find the module again.
+    pub fn set_transmission_mode(
+        &mut self,
+        transmission_mode: &TransmissionMode,
+    ) -> Result<(), Hc12Error> {
+        let new_baud_rate = command::set_transmission_mode(self, transmission_mode)?;
+        self.apply_transmission_mode(transmission_mode, new_baud_rate)
+    }
+
+    /// Changes baud rate and channel under a single SET toggle. The module only moves to the new
+    /// baud rate once it leaves command mode, so both commands go out at the current one. If the
+    /// channel is rejected the baud change is undone before leaving command mode, so module and
+    /// host stay on the current baud rate.
+    pub fn set_baud_and_channel(
+        &mut self,
+        baud_rate: &BaudRate,
+        channel: Channel,
+    ) -> Result<(), Hc12Error> {
+        self.validate_channel(channel)?;
+        let previous_baud_rate = self.state.baud_rate;
+
+        let result = {
+            let mut guard = CommandModeGuard::new(self)?;
+            command::set_baud_and_channel(&mut guard, baud_rate, channel, &previous_baud_rate)
+        };
+
+        self.finish_baud_and_channel(baud_rate, channel, result)
+    }
+
+    /// Runs `f` up to `attempts` times until it succeeds, waiting `base_delay_ms` after the first
+    /// failure and doubling the wait after every further one. Returns the last error once all
+    /// attempts failed.
+    pub fn with_retries<T>(
+        &mut self,
+        attempts: u8,
+        base_delay_ms: u32,
+        mut f: impl FnMut(&mut Self) -> Result<T, Hc12Error>,
+    ) -> Result<T, Hc12Error> {
+        let mut attempt = 0;
+        loop {
+            match f(self) {
+                Ok(value) => return Ok(value),
+                Err(error) if attempt + 1 >= attempts => return Err(error),
+                Err(_) => {
+                    self.delay
+                        .delay_ms(backoff_delay_ms(base_delay_ms, attempt));
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// [`Hc12::set_baud`] retried with [`Hc12::with_retries`].
+    pub fn set_baud_retry(
+        &mut self,
+        baud_rate: &BaudRate,
+        attempts: u8,
+        base_delay_ms: u32,
+    ) -> Result<(), Hc12Error> {
+        self.with_retries(attempts, base_delay_ms, |hc12| hc12.set_baud(baud_rate))
+    }
+
+    /// [`Hc12::set_channel`] retried with [`Hc12::with_retries`].
+    pub fn set_channel_retry(
+        &mut self,
+        channel: Channel,
+        attempts: u8,
+        base_delay_ms: u32,
+    ) -> Result<(), Hc12Error> {
+        self.with_retries(attempts, base_delay_ms, |hc12| hc12.set_channel(channel))
+    }
+
+    /// Sets the radio channel. FU2 and FU4 only support channels up to 100, higher channels are
+    /// rejected with [`Hc12Error::IncompatibleMode`] in these modes.
+    pub fn set_channel(&mut self, channel: Channel) -> Result<(), Hc12Error> {
+        self.validate_channel(channel)?;
+
+        command::set_channel(self, channel)?;
+        self.state.channel = channel;
+
+        Ok(())
+    }
+
+    /// Sends `command` in command mode and reads the module's raw response into `response` until
+    /// the module stops sending or `response` is full, returning the number of bytes received.
+    /// This covers informational commands with long or multi-line responses, e.g. `AT+RX` or
+    /// `AT+V`.
+    pub fn send_command_into(
+        &mut self,
+        command: &[u8],
+        response: &mut [u8],
+    ) -> Result<usize, Hc12Error> {
+        self.exchange(command, response)
+    }
+
+    /// Reads the channel the module is set to via `AT+RC`.
+    pub fn get_channel(&mut self) -> Result<Channel, Hc12Error> {
+        command::get_channel(self)
+    }
+
+    /// Like [`Hc12::set_channel`], but reads the channel back under the same SET toggle and fails
+    /// with [`Hc12Error::Channel`] if the module confirmed the change without applying it.
+    pub fn set_channel_verified(&mut self, channel: Channel) -> Result<(), Hc12Error> {
+        self.validate_channel(channel)?;
+
+        let read_back = {
+            let mut guard = CommandModeGuard::new(self)?;
+            command::set_channel(&mut guard, channel)?;
+            command::get_channel(&mut guard)?
+        };
+        if read_back != channel {
+            return Err(Hc12Error::Channel);
+        }
+        self.state.channel = channel;
+
+        Ok(())
+    }
+
+    pub fn set_transmit_power(&mut self, transmit_power: &TransmitPower) -> Result<(), Hc12Error> {
+        command::set_transmit_power(self, transmit_power)?;
+        self.state.transmit_power = *transmit_power;
+
+        Ok(())
+    }
+
+    /// [`Hc12::set_transmit_power`] for a [`PowerLevel`].
+    pub fn set_power(&mut self, power_level: PowerLevel) -> Result<(), Hc12Error> {
+        self.set_transmit_power(&power_level.into())
+    }
+
+    /// Measures how long the module takes to answer `AT`, from the start of the command until its
+    /// `OK` has arrived. Entering and leaving command mode is not included. Fails with
+    /// [`Hc12Error::Timeout`] if no answer arrives within one second.
+    pub fn measure_rtt(&mut self) -> Result<esp_hal::time::Duration, Hc12Error> {
+        const TIMEOUT_MS: u64 = 1000;
+
+        let mut buffer = [0u8; 14];
+        self.enter_command_mode()?;
+
+        let start = esp_hal::time::now();
+        let mut time_answer = || {
+            self.uart.write_bytes(b"AT")?;
+
+            // The line ending is not waited for as some clones don't send one.
+            let mut len = 0;
+            while len < 2 {
+                len += self.uart.read_buffered_bytes(&mut buffer[len..])?;
+                if len < 2 && (esp_hal::time::now() - start).to_millis() >= TIMEOUT_MS {
+                    return Err(Hc12Error::Timeout);
+                }
+            }
+
+            if !buffer[..len].starts_with(b"OK") {
+                return Err(Hc12Error::Test);
+            }
+
+            Ok(esp_hal::time::now() - start)
+        };
+        let result = time_answer();
+
+        self.exit_command_mode()?;
+        // Drop the rest of the response so it isn't mistaken for received data. A channel too busy
+        // to drain doesn't invalidate the measurement.
+        let _ = self.drain_rx();
+
+        result
+    }
+
+    /// Recovers a wedged module by toggling SET inactive, active and inactive again and confirming
+    /// that it answers `AT`. Unlike [`Hc12::set_default`] this leaves the stored configuration
+    /// untouched.
+    pub fn reset_command_mode(&mut self) -> Result<(), Hc12Error> {
+        self.require_no_pending_command()?;
+        self.require_set_pin()?;
+        self.drive_set(self.set.polarity.transparent_level())?;
+        self.delay.delay_ms(200);
+        self.drive_set(self.set.polarity.command_level())?;
+        self.delay.delay_ms(200);
+        self.drive_set(self.set.polarity.transparent_level())?;
+        self.delay.delay_ms(200);
+
+        self.test()
+    }
+
+    /// Reads the configuration the module actually runs with via `AT+RX`.
+    pub fn get_params(&mut self) -> Result<Hc12Params, Hc12Error> {
+        command::get_params(self)
+    }
+
+    /// Brings the tracked state back in line with the module after it was reset or reconfigured
+    /// behind the driver's back: finds its baud rate with `auto_baud`, reads its settings with
+    /// [`Hc12::get_params`] and returns the updated state.
+    pub fn resync(&mut self) -> Result<Hc12State, Hc12Error> {
+        self.auto_baud()?;
+        let params = self.get_params()?;
+
+        Ok(self.adopt_params(&params))
+    }
+
+    /// Reads the configuration with [`Hc12::get_params`] and logs it as a readable block when the
+    /// `log` or `defmt` feature is enabled.
+    pub fn dump_config(&mut self) -> Result<Hc12Params, Hc12Error> {
+        let params = self.get_params()?;
+        params.log();
+
+        Ok(params)
+    }
+
+    /// Restores the module's factory settings with `AT+DEFAULT`: 9600 baud, FU3, channel 1 and
+    /// 20 dBm (P8). The module switches to them right after answering, so on success the host UART
+    /// is moved to 9600 baud and [`Hc12::state`] reset to match, ready for the next command.
+    pub fn set_default(&mut self) -> Result<(), Hc12Error> {
+        command::set_default(self)?;
+        self.reset_state()
+    }
+
+    /// Detects the module's current baud rate and applies `target`: transmission mode first, as it
+    /// may force a baud rate change, then baud rate, channel and transmit power. Returns the baud
+    /// rate the module was found at. The target is validated before anything is sent, and applying
+    /// the same target again is harmless.
+    pub fn bring_up(&mut self, target: &Hc12Config) -> Result<BaudRate, Hc12Error> {
+        target.validate()?;
+
+        let detected_baud_rate = self.auto_baud()?;
+        self.set_transmission_mode(&target.transmission_mode)?;
+        if self.state.baud_rate != target.baud_rate {
+            self.set_baud(&target.baud_rate)?;
+        }
+        self.set_channel(target.channel)?;
+        self.set_transmit_power(&target.transmit_power)?;
+        self.state_known = true;
+
+        Ok(detected_baud_rate)
+    }
+
+    /// Applies `target` with only the commands that change something compared to
+    /// [`Hc12::state`], in the order of `bring_up`: transmission mode, baud rate, channel and
+    /// transmit power. Unless the state was established by `bring_up`, `resync`, `set_default` or
+    /// an earlier `reconfigure`, the module's settings are read with [`Hc12::get_params`] first.
+    /// Unlike `bring_up` it doesn't search for the baud rate. After a failure the state counts as
+    /// unknown again.
+    pub fn reconfigure(&mut self, target: &Hc12Config) -> Result<(), Hc12Error> {
+        target.validate()?;
+
+        let result = self.apply_changes(target);
+        self.state_known = result.is_ok();
+
+        result
+    }
+
+    fn apply_changes(&mut self, target: &Hc12Config) -> Result<(), Hc12Error> {
+        if !self.state_known {
+            let params = self.get_params()?;
+            self.adopt_params(&params);
+        }
+
+        if self.state.transmission_mode != target.transmission_mode {
+            self.set_transmission_mode(&target.transmission_mode)?;
+        }
+        if self.state.baud_rate != target.baud_rate {
+            self.set_baud(&target.baud_rate)?;
+        }
+        if self.state.channel != target.channel {
+            self.set_channel(target.channel)?;
+        }
+        if self.state.transmit_power != target.transmit_power {
+            self.set_transmit_power(&target.transmit_power)?;
+        }
+
+        Ok(())
+    }
+
+    /// Quiesces the driver before light sleep: a command started with one of the `poll_*` methods
+    /// is abandoned and the module returned to transparent mode, and queued bytes are sent, as the
+    /// TX FIFO stalls while the UART clock is off. Bytes in the RX buffer are kept for after the
+    /// sleep. The bytes that wake the chip through [`Hc12::rx_pin`] arrive while the UART is
+    /// still asleep and are lost or garbled, so the peer should lead with a preamble.
+    ///
+    /// ```ignore
+    /// hc12.prepare_for_light_sleep()?;
+    /// // Wake on a low level of GPIO `hc12.rx_pin()`, e.g. with `Rtc::sleep_light`.
+    /// rtc.sleep_light(&[&gpio_wakeup]);
+    /// hc12.resume_after_light_sleep(false)?;
+    /// ```
+    pub fn prepare_for_light_sleep(&mut self) -> Result<(), Hc12Error> {
+        if self.pending_command.take().is_some() {
+            self.exit_command_mode()?;
+        }
+
+        self.flush()
+    }
+
+    /// Re-arms the driver after [`Hc12::prepare_for_light_sleep`]. With `check_module`, the module
+    /// is asked for an `AT` first, which discards what was received so far.
+    pub fn resume_after_light_sleep(&mut self, check_module: bool) -> Result<(), Hc12Error> {
+        if check_module {
+            self.test()?;
+        }
+
+        Ok(())
+    }
+
+    /// Puts the module to sleep with `AT+SLEEP` once it leaves command mode. Asleep it draws
+    /// about 22 µA and neither sends nor receives until [`Hc12::wake`] or any other command.
+    pub fn sleep(&mut self) -> Result<(), Hc12Error> {
+        command::sleep(self)?;
+        self.state.asleep = true;
+
+        Ok(())
+    }
+
+    /// Wakes the module after [`Hc12::sleep`] by passing through command mode. Does nothing if
+    /// [`Hc12::state`] says it is awake.
+    pub fn wake(&mut self) -> Result<(), Hc12Error> {
+        if !self.state.asleep {
+            return Ok(());
+        }
+
+        self.enter_command_mode()?;
+        self.exit_command_mode()
+    }
+
+    /// Sends `payload` from a sleeping module and puts it back to sleep, for battery nodes
+    /// transmitting on a schedule: wakes the module and waits `wake_settle_ms` for its radio,
+    /// unless it is awake already, then writes and flushes `payload` and waits out its air time
+    /// before `AT+SLEEP`, as the module still has it buffered when the UART is done.
+    pub fn duty_cycle_send(
+        &mut self,
+        payload: &[u8],
+        wake_settle_ms: u32,
+    ) -> Result<(), Hc12Error> {
+        if self.state.asleep {
+            self.wake()?;
+            self.delay.delay_ms(wake_settle_ms);
+        }
+
+        self.write_all(payload)?;
+        self.flush()?;
+        self.delay.delay_ms(self.air_time_ms(payload.len()));
+
+        self.sleep()
+    }
+
+    pub fn write(&mut self, data: &[u8]) -> Result<usize, esp_hal::uart::Error> {
+        self.uart.write_bytes(data)
+    }
+
+    /// Same as [`Hc12::read_exact`].
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<(), esp_hal::uart::Error> {
+        self.read_exact(buffer)
+    }
+
+    /// Blocks until `buffer` is completely filled.
+    pub fn read_exact(&mut self, buffer: &mut [u8]) -> Result<(), esp_hal::uart::Error> {
+        let buffered = self.rx_buffer.pop_into(buffer);
+        self.uart.read_bytes(&mut buffer[buffered..])?;
+        self.note_rx(buffer.len() - buffered);
+
+        Ok(())
+    }
+
+    /// Like [`Hc12::read_exact`], but gives up once `timeout_ms` have passed. On failure the error
+    /// comes with the number of bytes already read into `buffer`, e.g. `(Hc12Error::Timeout, 3)`
+    /// if only three bytes arrived in time.
+    pub fn read_exact_timeout(
+        &mut self,
+        buffer: &mut [u8],
+        timeout_ms: u32,
+    ) -> Result<(), (Hc12Error, usize)> {
+        let start = esp_hal::time::now();
+        let mut len = 0;
+
+        while len < buffer.len() {
+            len += self
+                .try_read(&mut buffer[len..])
+                .map_err(|error| (error.into(), len))?;
+
+            if len < buffer.len()
+                && (esp_hal::time::now() - start).to_millis() >= u64::from(timeout_ms)
+            {
+                return Err((Hc12Error::Timeout, len));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Blocks until at least one byte has been received, then reads as many as are available and
+    /// fit into `buffer`.
+    pub fn read_some(&mut self, buffer: &mut [u8]) -> Result<usize, esp_hal::uart::Error> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let bytes_read = self.try_read(buffer)?;
+            if bytes_read != 0 {
+                return Ok(bytes_read);
+            }
+        }
+    }
+
+    /// Writes all of `data`, waiting for the TX FIFO to drain whenever the UART doesn't take the
+    /// rest at once. Fails with [`Hc12Error::DutyCycleExceeded`] without sending anything if the
+    /// duty-cycle budget doesn't cover it.
+    pub fn write_all(&mut self, mut data: &[u8]) -> Result<(), Hc12Error> {
+        self.reserve_air_time(data.len())
+            .map_err(|_| Hc12Error::DutyCycleExceeded)?;
+
+        while !data.is_empty() {
+            let bytes_written = self.uart.write_bytes(data)?;
+            data = &data[bytes_written..];
+            if !data.is_empty() {
+                self.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Blocks until the UART has sent everything written so far. Gives up with
+    /// [`Hc12Error::Timeout`] after twice the time a full TX FIFO takes at the current baud rate,
+    /// so a stalled UART can't hang the caller.
+    pub fn flush(&mut self) -> Result<(), Hc12Error> {
+        let timeout_ms = (UART_FIFO_LEN as u64 * 10 * 1000 * 2)
+            .div_ceil(u64::from(u32::from(self.state.baud_rate)));
+        let start = esp_hal::time::now();
+
+        loop {
+            match embedded_hal_nb::serial::Write::flush(&mut self.uart) {
+                Ok(()) => return Ok(()),
+                Err(nb::Error::Other(error)) => return Err(error.into()),
+                Err(nb::Error::WouldBlock) => {}
+            }
+
+            if (esp_hal::time::now() - start).to_millis() >= timeout_ms {
+                return Err(Hc12Error::Timeout);
+            }
+        }
+    }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum Hc12Error {
-    Test,
-    BaudRate,
-    AutoBaudRate,
-    TransmissionMode,
-    Default,
-    Config,
-    UartError(esp_hal::uart::Error),
-    InvalidResponse,
-}
+    /// Writes `data` in chunks of `chunk` bytes, flushing and waiting `gap_ms` after each chunk so
+    /// the module can send it over the air before the next one arrives.
+    pub fn write_paced(&mut self, data: &[u8], chunk: usize, gap_ms: u32) -> Result<(), Hc12Error> {
+        for data in data.chunks(chunk.max(1)) {
+            self.write_all(data)?;
+            self.flush()?;
+            self.delay.delay_ms(gap_ms);
+        }
 
-impl From<esp_hal::uart::Error> for Hc12Error {
-    fn from(error: esp_hal::uart::Error) -> Self {
-        Hc12Error::UartError(error)
+        Ok(())
     }
-}
 
-pub enum TransmissionMode {
-    Fu1,
-    Fu2,
-    Fu3,
-    Fu4,
-}
+    /// [`Hc12::write_paced`] in chunks of [`Hc12::max_chunk_bytes`], each followed by its air
+    /// time at the current mode and baud rate or the mode's minimum packet spacing, whichever is
+    /// longer, like [`Hc12::send_chunked`].
+    pub fn write_paced_default(&mut self, data: &[u8]) -> Result<(), Hc12Error> {
+        let chunk = self.max_chunk_bytes();
+        let gap_ms = self.burst_gap_ms(chunk);
 
-impl From<&TransmissionMode> for u32 {
-    fn from(transmission_mode: &TransmissionMode) -> Self {
-        match transmission_mode {
-            TransmissionMode::Fu1 => 1,
-            TransmissionMode::Fu2 => 2,
-            TransmissionMode::Fu3 => 3,
-            TransmissionMode::Fu4 => 4,
-        }
+        self.write_paced(data, chunk, gap_ms)
     }
-}
 
-impl From<TransmissionMode> for u32 {
-    fn from(transmission_mode: TransmissionMode) -> Self {
-        match transmission_mode {
-            TransmissionMode::Fu1 => 1,
-            TransmissionMode::Fu2 => 2,
-            TransmissionMode::Fu3 => 3,
-            TransmissionMode::Fu4 => 4,
+    /// Writes `data` in bursts of [`TransmissionMode::max_burst`] bytes for the current mode,
+    /// waiting after each burst until the module is ready for the next one.
+    pub fn send_chunked(&mut self, data: &[u8]) -> Result<(), Hc12Error> {
+        let chunk = self.state.transmission_mode.max_burst();
+        let gap_ms = self.burst_gap_ms(chunk);
+
+        self.write_paced(data, chunk, gap_ms)
+    }
+
+    /// Sends `data` like [`Hc12::send_chunked`], but only if the channel looks free: nothing has
+    /// been received but not yet read and the last byte arrived at least
+    /// [`Hc12::min_rx_idle_ms`] ago. Otherwise nothing is sent and [`Hc12Error::Busy`] is
+    /// returned, so the caller can back off instead of colliding with the peer.
+    pub fn try_send(&mut self, data: &[u8]) -> Result<(), Hc12Error> {
+        if self.channel_busy()? {
+            return Err(Hc12Error::Busy);
         }
+
+        self.send_chunked(data)
     }
-}
 
-#[derive(Default, Clone, Copy)]
-pub enum BaudRate {
-    Baud1200,
-    Baud2400,
-    Baud4800,
-    #[default]
-    Baud9600,
-    Baud19200,
-    Baud38400,
-    Baud57600,
-    Baud115200,
-}
+    pub fn send_line(&mut self, line: &str) -> Result<(), Hc12Error> {
+        let terminator: &[u8] = (&self.line_terminator).into();
 
-impl From<&BaudRate> for &str {
-    fn from(value: &BaudRate) -> Self {
-        match value {
-            BaudRate::Baud1200 => "1200",
-            BaudRate::Baud2400 => "2400",
-            BaudRate::Baud4800 => "4800",
-            BaudRate::Baud9600 => "9600",
-            BaudRate::Baud19200 => "19200",
-            BaudRate::Baud38400 => "38400",
-            BaudRate::Baud57600 => "57600",
-            BaudRate::Baud115200 => "115200",
-        }
+        self.write_all(line.as_bytes())?;
+        self.write_all(terminator)?;
+        self.flush()
     }
-}
 
-impl From<BaudRate> for u32 {
-    fn from(baud_rate: BaudRate) -> Self {
-        match baud_rate {
-            BaudRate::Baud1200 => 1200,
-            BaudRate::Baud2400 => 2400,
-            BaudRate::Baud4800 => 4800,
-            BaudRate::Baud9600 => 9600,
-            BaudRate::Baud19200 => 19200,
-            BaudRate::Baud38400 => 38400,
-            BaudRate::Baud57600 => 57600,
-            BaudRate::Baud115200 => 115200,
+    /// Reads into `buffer` until the configured line terminator is received and returns the line
+    /// without the terminator. Fails with [`Hc12Error::Timeout`] if no complete line arrives within
+    /// `timeout_ms`.
+    pub fn receive_line<'b>(
+        &mut self,
+        buffer: &'b mut [u8],
+        timeout_ms: u32,
+    ) -> Result<&'b str, Hc12Error> {
+        let terminator: &[u8] = (&self.line_terminator).into();
+        let start = esp_hal::time::now();
+        let mut len = 0;
+
+        loop {
+            if len == buffer.len() {
+                return Err(Hc12Error::BufferFull);
+            }
+
+            len += self.read_buffered(&mut buffer[len..len + 1])?;
+
+            if buffer[..len].ends_with(terminator) {
+                return core::str::from_utf8(&buffer[..len - terminator.len()])
+                    .map_err(|_| Hc12Error::InvalidResponse);
+            }
+
+            if (esp_hal::time::now() - start).to_millis() >= u64::from(timeout_ms) {
+                return Err(Hc12Error::Timeout);
+            }
         }
     }
 }
 
-impl From<&BaudRate> for u32 {
-    fn from(baud_rate: &BaudRate) -> Self {
-        match baud_rate {
-            BaudRate::Baud1200 => 1200,
-            BaudRate::Baud2400 => 2400,
-            BaudRate::Baud4800 => 4800,
-            BaudRate::Baud9600 => 9600,
-            BaudRate::Baud19200 => 19200,
-            BaudRate::Baud38400 => 38400,
-            BaudRate::Baud57600 => 57600,
-            BaudRate::Baud115200 => 115200,
+#[cfg(feature = "async")]
+impl<D, S: OutputPin> Hc12<'_, Async, D, S> {
+    /// Like [`Hc12::drain_rx`], but yields to other tasks between reads instead of spinning on
+    /// the FIFO.
+    async fn drain_rx_async(&mut self) -> Result<(), Hc12Error> {
+        self.rx_buffer.clear();
+
+        let deadline = Instant::now() + Duration::from_millis(self.timings.drain_timeout_ms.into());
+        let mut buffer = [0u8; 14];
+        while self
+            .uart
+            .read_buffered_bytes(&mut buffer)
+            .is_ok_and(|bytes_read: usize| bytes_read != 0)
+        {
+            if Instant::now() >= deadline {
+                return Err(Hc12Error::Busy);
+            }
+            Timer::after_millis(1).await;
         }
+
+        Ok(())
     }
-}
 
-pub struct Hc12<'d, Dm: esp_hal::DriverMode> {
-    uart: Uart<'d, Dm>,
-    set: OutputOpenDrain<'d>,
-}
+    /// Switches the module into command mode, after sending what is still queued and discarding
+    /// what was received, so AT commands can be written and their responses read with the plain
+    /// I/O methods. Nothing sent in command mode reaches the peer, and the built-in commands
+    /// toggle SET on their own, so call [`Hc12::exit_command_mode`] before using either.
+    pub async fn enter_command_mode(&mut self) -> Result<(), Hc12Error> {
+        self.require_set_pin()?;
+        // Data still in the TX FIFO would be taken for a command once SET switches.
+        self.uart.flush_async().await?;
+        self.drain_rx_async().await?;
 
-impl<Dm: DriverMode> Hc12<'_, Dm> {
-    pub fn read_buffered(&mut self, buffer: &mut [u8]) -> Result<usize, esp_hal::uart::Error> {
-        self.uart.read_buffered_bytes(buffer)
-    }
-}
+        // Command mode also wakes a sleeping module.
+        self.drive_set(self.set.polarity.command_level())?;
+        self.state.asleep = false;
+        Timer::after_millis(200).await;
 
-impl<'d> Hc12<'d, Blocking> {
-    pub fn new(
-        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
-        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
-        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
-        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
-    ) -> Result<Self, Hc12Error> {
-        let uart = Uart::new(uart, Default::default())
-            .map_err(|_| Hc12Error::Config)?
-            .with_rx(rx)
-            .with_tx(tx);
+        Ok(())
+    }
 
-        let mut set =
-            OutputOpenDrain::new(set, esp_hal::gpio::Level::Low, esp_hal::gpio::Pull::None);
-        set.set_high();
-        esp_hal::delay::Delay::new().delay_millis(200);
-        set.set_low();
-        esp_hal::delay::Delay::new().delay_millis(200);
+    /// Returns the module to transparent mode after [`Hc12::enter_command_mode`] and waits
+    /// [`Hc12Timings::post_command_settle_ms`].
+    pub async fn exit_command_mode(&mut self) -> Result<(), Hc12Error> {
+        self.drive_set(self.set.polarity.transparent_level())?;
+        Timer::after_millis(self.timings.post_command_settle_ms.into()).await;
 
-        Ok(Self { uart, set })
+        Ok(())
     }
-}
 
-impl<'d> Hc12<'d, Async> {
-    pub async fn new(
-        uart: impl Peripheral<P = esp_hal::uart::AnyUart> + 'd,
-        rx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
-        tx: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
-        set: impl Peripheral<P = esp_hal::gpio::AnyPin> + 'd,
-    ) -> Result<Self, Hc12Error> {
-        let uart = Uart::new(uart, Config::default())
-            .map_err(|_| Hc12Error::Config)?
-            .with_rx(rx)
-            .with_tx(tx)
-            .into_async();
-
-        let mut set =
-            OutputOpenDrain::new(set, esp_hal::gpio::Level::Low, esp_hal::gpio::Pull::None);
-        set.set_high();
-        Timer::after_millis(200).await;
+    /// Sends `command` while already in command mode and returns the raw response.
+    async fn exchange_command(&mut self, command: &str) -> Result<Vec<u8, COMMAND_LEN>, Hc12Error> {
+        let mut buffer = [0u8; COMMAND_LEN];
+        let bytes_read = self.exchange_raw(command.as_bytes(), &mut buffer).await?;
 
-        Ok(Self { uart, set })
+        Ok(Vec::from_slice(&buffer[..bytes_read]).unwrap())
     }
-}
 
-impl Hc12<'_, Blocking> {
-    fn send_command<const N: usize>(
+    /// Sends `command` while already in command mode and reads the raw response into `response`,
+    /// returning its length.
+    async fn exchange_raw(
         &mut self,
-        command: &String<N>,
-    ) -> Result<String<14>, Hc12Error> {
-        let mut buffer = [0u8; 14];
-        while self
-            .uart
-            .read_buffered_bytes(&mut buffer)
-            .is_ok_and(|bytes_read: usize| bytes_read != 0)
-        {}
+        command: &[u8],
+        response: &mut [u8],
+    ) -> Result<usize, Hc12Error> {
+        trace!("sending command {:?}", command);
+        self.uart.write_bytes(command)?;
+        self.uart.flush_async().await?;
+        Timer::after_millis(200).await;
+
+        let mut bytes_read = 0;
+        loop {
+            let chunk = self.uart.read_buffered_bytes(&mut response[bytes_read..])?;
+            bytes_read += chunk;
+            if chunk == 0 || bytes_read == response.len() {
+                break;
+            }
+            Timer::after_millis(u64::from(RESPONSE_IDLE_MS)).await;
+        }
+        trace!("received response {:?}", &response[..bytes_read]);
+        self.store_response(&response[..bytes_read]);
 
-        self.set.set_low();
-        esp_hal::delay::Delay::new().delay_millis(200);
+        Ok(bytes_read)
+    }
 
-        self.uart.write_bytes(command.as_bytes())?;
-        esp_hal::delay::Delay::new().delay_millis(200);
+    /// Sends `command` in command mode and reads the module's raw response into `response` until
+    /// the module stops sending or `response` is full, returning the number of bytes received.
+    /// This covers informational commands with long or multi-line responses, e.g. `AT+RX` or
+    /// `AT+V`.
+    pub async fn send_command_into(
+        &mut self,
+        command: &[u8],
+        response: &mut [u8],
+    ) -> Result<usize, Hc12Error> {
+        self.enter_command_mode().await?;
+        let result = self.exchange_raw(command, response).await;
+        self.exit_command_mode().await?;
+
+        result
+    }
 
-        let bytes_read = self.uart.read_buffered_bytes(&mut buffer)?;
-        self.set.set_high();
-        esp_hal::delay::Delay::new().delay_millis(200);
+    async fn send_command(&mut self, command: &str) -> Result<Vec<u8, COMMAND_LEN>, Hc12Error> {
+        self.enter_command_mode().await?;
+        let result = self.exchange_command(command).await;
+        self.exit_command_mode().await?;
 
-        String::from_utf8(Vec::from_slice(&buffer[0..bytes_read]).unwrap())
-            .map_err(|_| Hc12Error::InvalidResponse)
+        result
     }
 
-    pub fn test(&mut self) -> Result<(), Hc12Error> {
-        let mut command = String::<14>::new();
-        command.push_str("AT").unwrap();
-        let result = self.send_command(&command)?;
+    /// Changes the channel in command mode, failing with [`Hc12Error::Channel`] if the module
+    /// doesn't confirm it.
+    async fn exchange_channel(&mut self, channel: Channel) -> Result<(), Hc12Error> {
+        let response = self
+            .exchange_command(&protocol::format_set_channel(channel)?)
+            .await?;
 
-        if result != "OK\r\n" {
-            return Err(Hc12Error::Test);
+        if protocol::parse_channel_response(&response)? != channel {
+            return Err(Hc12Error::Channel);
         }
 
         Ok(())
     }
 
-    pub fn auto_baud(&mut self) -> Result<BaudRate, Hc12Error> {
-        for baud_rate in [
-            BaudRate::Baud1200,
-            BaudRate::Baud2400,
-            BaudRate::Baud4800,
-            BaudRate::Baud9600,
-            BaudRate::Baud19200,
-            BaudRate::Baud38400,
-            BaudRate::Baud57600,
-            BaudRate::Baud115200,
-        ] {
-            self.uart
-                .set_config(&Config::default().with_baudrate(u32::from(baud_rate)))
-                .unwrap();
-            esp_hal::delay::Delay::new().delay_millis(40);
-
-            if self.test().is_ok() {
-                return Ok(baud_rate);
-            }
+    /// Changes the baud rate in command mode, failing with [`Hc12Error::BaudRate`] if the module
+    /// doesn't confirm it. The module only moves to the new baud rate once it leaves command mode.
+    async fn exchange_baud(&mut self, baud_rate: &BaudRate) -> Result<(), Hc12Error> {
+        let response = self
+            .exchange_command(&protocol::format_set_baud(baud_rate)?)
+            .await?;
+
+        if protocol::parse_baud_response(&response)? != *baud_rate {
+            return Err(Hc12Error::BaudRate);
         }
 
-        Err(Hc12Error::AutoBaudRate)
+        Ok(())
     }
 
-    pub fn set_baud(&mut self, baud_rate: &BaudRate) -> Result<(), Hc12Error> {
-        let mut command = String::<14>::new();
-        write!(command, "AT+B{}", u32::from(baud_rate)).unwrap();
+    pub async fn test(&mut self) -> Result<(), Hc12Error> {
+        protocol::parse_test_response(&self.send_command(protocol::TEST_COMMAND).await?)
+    }
 
-        let result = self.send_command(&command)?;
-        self.uart
-            .set_config(&Config::default().with_baudrate(u32::from(baud_rate)))
-            .map_err(|_| Hc12Error::TransmissionMode)?;
+    /// Finds the module's baud rate by probing every rate with `AT` and follows it. Fails with
+    /// [`Hc12Error::AutoBaudRate`] if none answers, telling a silent line from garbage or a
+    /// garbled answer, and logs which it was; [`PoweredHc12::recover`](crate::PoweredHc12::recover)
+    /// can rescue a module that answers garbage.
+    pub async fn auto_baud(&mut self) -> Result<BaudRate, Hc12Error> {
+        let mut failure = AutoBaudFailure::NoResponseAtAnyBaud;
+        for baud_rate in command::PROBE_BAUD_RATES {
+            self.set_host_baud(baud_rate)
+                .map_err(|_| Hc12Error::Config)?;
+            Timer::after_millis(baud_rate.probe_settle_ms().into()).await;
+
+            self.last_response.clear();
+            match self.test().await {
+                Ok(()) => return Ok(baud_rate),
+                Err(error) => {
+                    let probe = AutoBaudFailure::classify(baud_rate, &error, &self.last_response);
+                    failure = failure.merge(probe);
+                }
+            }
+        }
 
-        let mut expected_response = String::<14>::new();
-        write!(expected_response, "OK+B{}\r\n", u32::from(baud_rate)).unwrap();
+        failure.log();
+        Err(Hc12Error::AutoBaudRate(failure))
+    }
 
-        if result != expected_response {
+    pub async fn set_baud(&mut self, baud_rate: &BaudRate) -> Result<(), Hc12Error> {
+        let response = self
+            .send_command(&protocol::format_set_baud(baud_rate)?)
+            .await?;
+        if protocol::parse_baud_response(&response)? != *baud_rate {
             return Err(Hc12Error::BaudRate);
         }
 
+        // Bytes still in the TX FIFO would go out at the new rate once the UART is reconfigured.
+        self.uart.flush_async().await?;
+        self.set_host_baud(*baud_rate)
+            .map_err(|_| Hc12Error::TransmissionMode)?;
+        Timer::after_millis(u64::from(BAUD_SWITCH_SETTLE_MS)).await;
+
         Ok(())
     }
 
-    pub fn set_transmission_mode(
+    /// Switches the transmission mode and follows the module to the baud rate the mode forces,
+    /// e.g. 1200 baud for FU4. If the module's report of that baud rate can't be parsed, the mode
+    /// change is still tracked but the host UART stays put and [`Hc12Error::InvalidResponse`] is
+    /// returned; use `auto_baud` to find the module again.
+    pub async fn set_transmission_mode(
         &mut self,
         transmission_mode: &TransmissionMode,
     ) -> Result<(), Hc12Error> {
-        let mut command = String::<14>::new();
-        write!(command, "AT+FU{}", u32::from(transmission_mode)).unwrap();
+        let response = self
+            .send_command(&protocol::format_set_transmission_mode(transmission_mode)?)
+            .await?;
 
-        let result = self.send_command(&command)?;
+        let new_baud_rate = command::confirm_transmission_mode(&response, transmission_mode)?;
+        self.apply_transmission_mode(transmission_mode, new_baud_rate)
+    }
 
-        let mut expected_response = String::<14>::new();
-        write!(expected_response, "OK+FU{}", u32::from(transmission_mode)).unwrap();
+    /// Changes baud rate and channel under a single SET toggle. The module only moves to the new
+    /// baud rate once it leaves command mode, so both commands go out at the current one. If the
+    /// channel is rejected the baud change is undone before leaving command mode, so module and
+    /// host stay on the current baud rate.
+    pub async fn set_baud_and_channel(
+        &mut self,
+        baud_rate: &BaudRate,
+        channel: Channel,
+    ) -> Result<(), Hc12Error> {
+        self.validate_channel(channel)?;
+        let previous_baud_rate = self.state.baud_rate;
+
+        self.enter_command_mode().await?;
+        let result = match self.exchange_baud(baud_rate).await {
+            Ok(()) => match self.exchange_channel(channel).await {
+                Ok(()) => Ok(()),
+                Err(error) => {
+                    let reverted = self.exchange_baud(&previous_baud_rate).await.is_ok();
+                    Err((error, reverted))
+                }
+            },
+            Err(error) => Err((error, true)),
+        };
+        // The state has to follow the module even if SET can't be released.
+        let exited = self.exit_command_mode().await;
+        self.finish_baud_and_channel(baud_rate, channel, result)?;
+
+        exited
+    }
 
-        let mut splitted = result.split(",");
-        if splitted
-            .next()
-            .is_none_or(|result| !result.starts_with(expected_response.as_str()))
-        {
-            return Err(Hc12Error::TransmissionMode);
+    /// Runs `f` up to `attempts` times until it succeeds, waiting `base_delay_ms` after the first
+    /// failure and doubling the wait after every further one. Returns the last error once all
+    /// attempts failed.
+    pub async fn with_retries<T>(
+        &mut self,
+        attempts: u8,
+        base_delay_ms: u32,
+        mut f: impl AsyncFnMut(&mut Self) -> Result<T, Hc12Error>,
+    ) -> Result<T, Hc12Error> {
+        let mut attempt = 0;
+        loop {
+            match f(self).await {
+                Ok(value) => return Ok(value),
+                Err(error) if attempt + 1 >= attempts => return Err(error),
+                Err(_) => {
+                    Timer::after_millis(backoff_delay_ms(base_delay_ms, attempt).into()).await;
+                    attempt += 1;
+                }
+            }
         }
+    }
 
-        if let Some(new_baud_rate) = splitted.next() {
-            let new_baud_rate = new_baud_rate[1..].trim();
-            self.uart
-                .set_config(&Config::default().with_baudrate(str::parse(new_baud_rate).unwrap()))
-                .map_err(|_| Hc12Error::TransmissionMode)?;
-        }
+    /// [`Hc12::set_baud`] retried with [`Hc12::with_retries`].
+    pub async fn set_baud_retry(
+        &mut self,
+        baud_rate: &BaudRate,
+        attempts: u8,
+        base_delay_ms: u32,
+    ) -> Result<(), Hc12Error> {
+        self.with_retries(attempts, base_delay_ms, async |hc12: &mut Self| {
+            hc12.set_baud(baud_rate).await
+        })
+        .await
+    }
+
+    /// [`Hc12::set_channel`] retried with [`Hc12::with_retries`].
+    pub async fn set_channel_retry(
+        &mut self,
+        channel: Channel,
+        attempts: u8,
+        base_delay_ms: u32,
+    ) -> Result<(), Hc12Error> {
+        self.with_retries(attempts, base_delay_ms, async |hc12: &mut Self| {
+            hc12.set_channel(channel).await
+        })
+        .await
+    }
+
+    /// Sets the radio channel. FU2 and FU4 only support channels up to 100, higher channels are
+    /// rejected with [`Hc12Error::IncompatibleMode`] in these modes.
+    pub async fn set_channel(&mut self, channel: Channel) -> Result<(), Hc12Error> {
+        self.validate_channel(channel)?;
+
+        self.enter_command_mode().await?;
+        let result = self.exchange_channel(channel).await;
+        self.exit_command_mode().await?;
+
+        result?;
+        self.state.channel = channel;
 
         Ok(())
     }
 
-    pub fn set_default(&mut self) -> Result<(), Hc12Error> {
-        let mut command = String::<14>::new();
-        write!(command, "AT+DEFAULT").unwrap();
+    /// Reads the channel the module is set to via `AT+RC`.
+    pub async fn get_channel(&mut self) -> Result<Channel, Hc12Error> {
+        protocol::parse_get_channel_response(
+            &self.send_command(protocol::GET_CHANNEL_COMMAND).await?,
+        )
+    }
 
-        let result = self
-            .send_command(&command)
-            .map_err(|_| Hc12Error::Default)?;
+    /// Like [`Hc12::set_channel`], but reads the channel back under the same SET toggle and fails
+    /// with [`Hc12Error::Channel`] if the module confirmed the change without applying it.
+    pub async fn set_channel_verified(&mut self, channel: Channel) -> Result<(), Hc12Error> {
+        self.validate_channel(channel)?;
 
-        if result != ("OK+DEFAULT\r\n") {
-            return Err(Hc12Error::Default);
+        self.enter_command_mode().await?;
+        let result = match self.exchange_channel(channel).await {
+            Ok(()) => self.exchange_command(protocol::GET_CHANNEL_COMMAND).await,
+            Err(error) => Err(error),
+        };
+        self.exit_command_mode().await?;
+
+        if protocol::parse_get_channel_response(&result?)? != channel {
+            return Err(Hc12Error::Channel);
         }
+        self.state.channel = channel;
 
         Ok(())
     }
 
-    pub async fn write(&mut self, data: &[u8]) -> Result<usize, esp_hal::uart::Error> {
-        self.uart.write_bytes(data)
+    pub async fn set_transmit_power(
+        &mut self,
+        transmit_power: &TransmitPower,
+    ) -> Result<(), Hc12Error> {
+        let response = self
+            .send_command(&protocol::format_set_transmit_power(transmit_power)?)
+            .await?;
+
+        if protocol::parse_transmit_power_response(&response)? != *transmit_power {
+            return Err(Hc12Error::TransmitPower);
+        }
+        self.state.transmit_power = *transmit_power;
+
+        Ok(())
     }
 
-    pub async fn read(&mut self, buffer: &mut [u8]) -> Result<(), esp_hal::uart::Error> {
-        self.uart.read_bytes(buffer)
+    /// [`Hc12::set_transmit_power`] for a [`PowerLevel`].
+    pub async fn set_power(&mut self, power_level: PowerLevel) -> Result<(), Hc12Error> {
+        self.set_transmit_power(&power_level.into()).await
     }
-}
 
-impl Hc12<'_, Async> {
-    async fn send_command<const N: usize>(
-        &mut self,
-        command: &String<N>,
-    ) -> Result<String<14>, Hc12Error> {
+    /// Measures how long the module takes to answer `AT`, from the start of the command until its
+    /// `OK` has arrived. Entering and leaving command mode is not included. Fails with
+    /// [`Hc12Error::Timeout`] if no answer arrives within one second.
+    pub async fn measure_rtt(&mut self) -> Result<Duration, Hc12Error> {
         let mut buffer = [0u8; 14];
-        while self
-            .uart
-            .read_buffered_bytes(&mut buffer)
-            .is_ok_and(|bytes_read: usize| bytes_read != 0)
-        {}
+        self.enter_command_mode().await?;
+
+        let start = Instant::now();
+        let deadline = start + Duration::from_secs(1);
+        let result = async {
+            self.uart.write_bytes(b"AT")?;
+
+            // The line ending is not waited for as some clones don't send one.
+            let mut len = 0;
+            while len < 2 {
+                len += with_deadline(deadline, self.uart.read_async(&mut buffer[len..]))
+                    .await
+                    .map_err(|_| Hc12Error::Timeout)??;
+            }
 
-        self.set.set_low();
-        Timer::after_millis(200).await;
+            if !buffer[..len].starts_with(b"OK") {
+                return Err(Hc12Error::Test);
+            }
 
-        self.uart.write_bytes(command.as_bytes())?;
-        self.uart.flush_async().await?;
-        Timer::after_millis(200).await;
+            Ok(start.elapsed())
+        }
+        .await;
 
-        let bytes_read = self.uart.read_buffered_bytes(&mut buffer)?;
-        self.set.set_high();
+        self.exit_command_mode().await?;
+        // Drop the rest of the response so it isn't mistaken for received data. A channel too busy
+        // to drain doesn't invalidate the measurement.
+        let _ = self.drain_rx_async().await;
+
+        result
+    }
+
+    /// Recovers a wedged module by toggling SET inactive, active and inactive again and confirming
+    /// that it answers `AT`. Unlike [`Hc12::set_default`] this leaves the stored configuration
+    /// untouched.
+    pub async fn reset_command_mode(&mut self) -> Result<(), Hc12Error> {
+        self.require_set_pin()?;
+        self.drive_set(self.set.polarity.transparent_level())?;
+        Timer::after_millis(200).await;
+        self.drive_set(self.set.polarity.command_level())?;
+        Timer::after_millis(200).await;
+        self.drive_set(self.set.polarity.transparent_level())?;
         Timer::after_millis(200).await;
 
-        String::from_utf8(Vec::from_slice(&buffer[0..bytes_read]).unwrap())
-            .map_err(|_| Hc12Error::InvalidResponse)
+        self.test().await
     }
 
-    pub async fn test(&mut self) -> Result<(), Hc12Error> {
-        let mut command = String::<14>::new();
-        command.push_str("AT").unwrap();
-        let result = self.send_command(&command).await?;
+    /// Reads the configuration the module actually runs with via `AT+RX`.
+    pub async fn get_params(&mut self) -> Result<Hc12Params, Hc12Error> {
+        let mut buffer = [0u8; PARAMS_RESPONSE_LEN];
+        let bytes_read = self
+            .send_command_into(protocol::GET_PARAMS_COMMAND.as_bytes(), &mut buffer)
+            .await?;
+
+        protocol::parse_params_response(&buffer[..bytes_read])
+    }
 
-        if result != "OK\r\n" {
-            return Err(Hc12Error::Test);
+    /// Brings the tracked state back in line with the module after it was reset or reconfigured
+    /// behind the driver's back: finds its baud rate with `auto_baud`, reads its settings with
+    /// [`Hc12::get_params`] and returns the updated state.
+    pub async fn resync(&mut self) -> Result<Hc12State, Hc12Error> {
+        self.auto_baud().await?;
+        let params = self.get_params().await?;
+
+        Ok(self.adopt_params(&params))
+    }
+
+    /// Reads the configuration with [`Hc12::get_params`] and logs it as a readable block when the
+    /// `log` or `defmt` feature is enabled.
+    pub async fn dump_config(&mut self) -> Result<Hc12Params, Hc12Error> {
+        let params = self.get_params().await?;
+        params.log();
+
+        Ok(params)
+    }
+
+    /// Restores the module's factory settings with `AT+DEFAULT`: 9600 baud, FU3, channel 1 and
+    /// 20 dBm (P8). The module switches to them right after answering, so on success the host UART
+    /// is moved to 9600 baud and [`Hc12::state`] reset to match, ready for the next command.
+    pub async fn set_default(&mut self) -> Result<(), Hc12Error> {
+        let response = self
+            .send_command(protocol::DEFAULT_COMMAND)
+            .await
+            .map_err(|_| Hc12Error::Default)?;
+        protocol::parse_default_response(&response)?;
+
+        self.reset_state()
+    }
+
+    /// Detects the module's current baud rate and applies `target`: transmission mode first, as it
+    /// may force a baud rate change, then baud rate, channel and transmit power. Returns the baud
+    /// rate the module was found at. The target is validated before anything is sent, and applying
+    /// the same target again is harmless.
+    pub async fn bring_up(&mut self, target: &Hc12Config) -> Result<BaudRate, Hc12Error> {
+        target.validate()?;
+
+        let detected_baud_rate = self.auto_baud().await?;
+        self.set_transmission_mode(&target.transmission_mode)
+            .await?;
+        if self.state.baud_rate != target.baud_rate {
+            self.set_baud(&target.baud_rate).await?;
         }
+        self.set_channel(target.channel).await?;
+        self.set_transmit_power(&target.transmit_power).await?;
+        self.state_known = true;
 
-        Ok(())
+        Ok(detected_baud_rate)
     }
 
-    pub async fn auto_baud(&mut self) -> Result<BaudRate, Hc12Error> {
-        for baud_rate in [
-            BaudRate::Baud1200,
-            BaudRate::Baud2400,
-            BaudRate::Baud4800,
-            BaudRate::Baud9600,
-            BaudRate::Baud19200,
-            BaudRate::Baud38400,
-            BaudRate::Baud57600,
-            BaudRate::Baud115200,
-        ] {
-            self.uart
-                .set_config(&Config::default().with_baudrate(u32::from(baud_rate)))
-                .unwrap();
-            Timer::after_millis(40).await;
-
-            if self.test().await.is_ok() {
-                return Ok(baud_rate);
-            }
+    /// Applies `target` with only the commands that change something compared to
+    /// [`Hc12::state`], in the order of `bring_up`: transmission mode, baud rate, channel and
+    /// transmit power. Unless the state was established by `bring_up`, `resync`, `set_default` or
+    /// an earlier `reconfigure`, the module's settings are read with [`Hc12::get_params`] first.
+    /// Unlike `bring_up` it doesn't search for the baud rate. After a failure the state counts as
+    /// unknown again.
+    pub async fn reconfigure(&mut self, target: &Hc12Config) -> Result<(), Hc12Error> {
+        target.validate()?;
+
+        let result = self.apply_changes(target).await;
+        self.state_known = result.is_ok();
+
+        result
+    }
+
+    async fn apply_changes(&mut self, target: &Hc12Config) -> Result<(), Hc12Error> {
+        if !self.state_known {
+            let params = self.get_params().await?;
+            self.adopt_params(&params);
+        }
+
+        if self.state.transmission_mode != target.transmission_mode {
+            self.set_transmission_mode(&target.transmission_mode)
+                .await?;
+        }
+        if self.state.baud_rate != target.baud_rate {
+            self.set_baud(&target.baud_rate).await?;
+        }
+        if self.state.channel != target.channel {
+            self.set_channel(target.channel).await?;
+        }
+        if self.state.transmit_power != target.transmit_power {
+            self.set_transmit_power(&target.transmit_power).await?;
         }
 
-        Err(Hc12Error::AutoBaudRate)
+        Ok(())
     }
 
-    pub async fn set_baud(&mut self, baud_rate: &BaudRate) -> Result<(), Hc12Error> {
-        let mut command = String::<14>::new();
-        write!(command, "AT+B{}", u32::from(baud_rate)).unwrap();
+    pub async fn write_async(&mut self, data: &[u8]) -> Result<usize, esp_hal::uart::Error> {
+        self.uart.write_async(data).await
+    }
 
-        let result = self.send_command(&command).await?;
-        self.uart
-            .set_config(&Config::default().with_baudrate(u32::from(baud_rate)))
-            .map_err(|_| Hc12Error::TransmissionMode)?;
+    /// Writes all of `data`, first waiting until the duty-cycle budget covers it. Fails with
+    /// [`Hc12Error::DutyCycleExceeded`] if `data` takes more air time than a full budget.
+    pub async fn write_all_async(&mut self, mut data: &[u8]) -> Result<(), Hc12Error> {
+        while let Err(wait_ms) = self.reserve_air_time(data.len()) {
+            let wait_ms = wait_ms.ok_or(Hc12Error::DutyCycleExceeded)?;
+            Timer::after_millis(u64::from(wait_ms)).await;
+        }
 
-        let mut expected_response = String::<14>::new();
-        write!(expected_response, "OK+B{}\r\n", u32::from(baud_rate)).unwrap();
+        while !data.is_empty() {
+            let bytes_written = self.uart.write_async(data).await?;
+            data = &data[bytes_written..];
+        }
 
-        if result != expected_response {
-            return Err(Hc12Error::BaudRate);
+        Ok(())
+    }
+
+    /// Quiesces the driver before light sleep by sending the queued bytes, as the TX FIFO stalls
+    /// while the UART clock is off. Bytes in the RX buffer are kept. As in blocking mode, the
+    /// bytes that wake the chip are lost; see [`Hc12::rx_pin`].
+    pub async fn prepare_for_light_sleep(&mut self) -> Result<(), Hc12Error> {
+        Ok(self.uart.flush_async().await?)
+    }
+
+    /// Re-arms the driver after [`Hc12::prepare_for_light_sleep`]. With `check_module`, the module
+    /// is asked for an `AT` first, which discards what was received so far.
+    pub async fn resume_after_light_sleep(&mut self, check_module: bool) -> Result<(), Hc12Error> {
+        if check_module {
+            self.test().await?;
         }
 
         Ok(())
     }
 
-    pub async fn set_transmission_mode(
+    /// Puts the module to sleep with `AT+SLEEP` once it leaves command mode. Asleep it draws
+    /// about 22 µA and neither sends nor receives until [`Hc12::wake`] or any other command.
+    pub async fn sleep(&mut self) -> Result<(), Hc12Error> {
+        protocol::parse_sleep_response(&self.send_command(protocol::SLEEP_COMMAND).await?)?;
+        self.state.asleep = true;
+
+        Ok(())
+    }
+
+    /// Wakes the module after [`Hc12::sleep`] by passing through command mode. Does nothing if
+    /// [`Hc12::state`] says it is awake.
+    pub async fn wake(&mut self) -> Result<(), Hc12Error> {
+        if !self.state.asleep {
+            return Ok(());
+        }
+
+        self.enter_command_mode().await?;
+        self.exit_command_mode().await
+    }
+
+    /// Sends `payload` from a sleeping module and puts it back to sleep, for battery nodes
+    /// transmitting on a schedule: wakes the module and waits `wake_settle_ms` for its radio,
+    /// unless it is awake already, then writes and flushes `payload` and waits out its air time
+    /// before `AT+SLEEP`, as the module still has it buffered when the UART is done.
+    pub async fn duty_cycle_send(
         &mut self,
-        transmission_mode: &TransmissionMode,
+        payload: &[u8],
+        wake_settle_ms: u32,
     ) -> Result<(), Hc12Error> {
-        let mut command = String::<14>::new();
-        write!(command, "AT+FU{}", u32::from(transmission_mode)).unwrap();
+        if self.state.asleep {
+            self.wake().await?;
+            Timer::after_millis(wake_settle_ms.into()).await;
+        }
+
+        self.write_all_async(payload).await?;
+        self.uart.flush_async().await?;
+        Timer::after_millis(self.air_time_ms(payload.len()).into()).await;
 
-        let result = self.send_command(&command).await?;
+        self.sleep().await
+    }
 
-        let mut expected_response = String::<14>::new();
-        write!(expected_response, "OK+FU{}", u32::from(transmission_mode)).unwrap();
+    /// Re-enables the interrupt of this driver's UART at `priority` instead of the one esp-hal
+    /// picked when the UART went async. The async handler stays bound, as esp-hal doesn't allow
+    /// replacing it without breaking the async reads and writes. At a low priority,
+    /// [`Hc12::read_async`] and the other waits only wake once the more urgent interrupts are
+    /// done, so the RX FIFO has to absorb that latency: at 115200 baud, raise the RX FIFO
+    /// threshold margin with [`Hc12::set_rx_fifo_full_threshold`].
+    ///
+    /// Fails with [`Hc12Error::Config`] if the UART was set up outside the driver, e.g. for
+    /// [`Hc12::from_uart`], as the driver doesn't know which UART it is.
+    pub fn set_interrupt_priority(&mut self, priority: Priority) -> Result<(), Hc12Error> {
+        let wiring = self.wiring.ok_or(Hc12Error::Config)?;
+        esp_hal::interrupt::enable(wiring.interrupt, priority).map_err(|_| Hc12Error::Config)
+    }
 
-        let mut splitted = result.split(",");
-        if splitted
-            .next()
-            .is_none_or(|result| !result.starts_with(expected_response.as_str()))
-        {
-            return Err(Hc12Error::TransmissionMode);
+    pub async fn flush_async(&mut self) -> Result<(), esp_hal::uart::Error> {
+        self.uart.flush_async().await
+    }
+
+    /// Waits for received bytes and reads as many as fit into `buffer`.
+    ///
+    /// This is cancellation safe: bytes are only taken from the RX buffer or the UART FIFO
+    /// when the future completes, so dropping it, e.g. because another branch of a `select` won,
+    /// loses nothing. The same holds for [`Hc12::wait_for_data`], [`Hc12::receive_line`] and
+    /// [`Hc12Framed::recv_frame`](crate::Hc12Framed::recv_frame) up to the point where it
+    /// acknowledges a reliable frame.
+    pub async fn read_async(&mut self, buffer: &mut [u8]) -> Result<usize, esp_hal::uart::Error> {
+        let buffered = self.rx_buffer.pop_into(buffer);
+        if buffered != 0 {
+            return Ok(buffered);
         }
 
-        if let Some(new_baud_rate) = splitted.next() {
-            let new_baud_rate = new_baud_rate[1..].trim();
-            self.uart
-                .set_config(&Config::default().with_baudrate(str::parse(new_baud_rate).unwrap()))
-                .map_err(|_| Hc12Error::TransmissionMode)?;
+        let bytes_read = self.uart.read_async(buffer).await?;
+        self.note_rx(bytes_read);
+
+        Ok(bytes_read)
+    }
+
+    /// Same as [`Hc12::read_async`], under the name of its blocking counterpart.
+    pub async fn read_some(&mut self, buffer: &mut [u8]) -> Result<usize, esp_hal::uart::Error> {
+        self.read_async(buffer).await
+    }
+
+    /// Waits until `buffer` is filled.
+    pub async fn read_exact(&mut self, buffer: &mut [u8]) -> Result<(), esp_hal::uart::Error> {
+        let mut len = 0;
+        while len < buffer.len() {
+            len += self.read_async(&mut buffer[len..]).await?;
         }
 
         Ok(())
     }
 
-    pub async fn set_default(&mut self) -> Result<(), Hc12Error> {
-        let mut command = String::<14>::new();
-        write!(command, "AT+DEFAULT").unwrap();
+    /// Like [`Hc12::read_exact`], but gives up once `timeout_ms` have passed. On failure the error
+    /// comes with the number of bytes already read into `buffer`, e.g. `(Hc12Error::Timeout, 3)`
+    /// if only three bytes arrived in time.
+    pub async fn read_exact_timeout(
+        &mut self,
+        buffer: &mut [u8],
+        timeout_ms: u32,
+    ) -> Result<(), (Hc12Error, usize)> {
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms.into());
+        let mut len = 0;
+
+        while len < buffer.len() {
+            len += with_deadline(deadline, self.read_async(&mut buffer[len..]))
+                .await
+                .map_err(|_| (Hc12Error::Timeout, len))?
+                .map_err(|error| (error.into(), len))?;
+        }
 
-        let result = self
-            .send_command(&command)
-            .await
-            .map_err(|_| Hc12Error::Default)?;
+        Ok(())
+    }
+
+    /// Waits until at least one received byte is available without consuming it, so a task can
+    /// `select` on incoming data and decide afterwards how to read it. The bytes the wait pulls out
+    /// of the UART are kept in the RX buffer and returned by the next read; cancelling the wait
+    /// loses nothing.
+    pub async fn wait_for_data(&mut self) -> Result<(), esp_hal::uart::Error> {
+        if !self.rx_buffer.is_empty() {
+            return Ok(());
+        }
+
+        self.fill_rx_buffer().await
+    }
 
-        if result != ("OK+DEFAULT\r\n") {
-            return Err(Hc12Error::Default);
+    /// Waits until the delimiter set with [`Hc12::set_delimiter`] is in the RX buffer, so the frame
+    /// it ends can be read without waiting. Without a delimiter it waits for any data, like
+    /// [`Hc12::wait_for_data`]. Fails with [`Hc12Error::BufferFull`] if the RX buffer fills up
+    /// before the delimiter arrives; its contents are kept. Cancelling the wait loses nothing.
+    pub async fn wait_for_delimiter(&mut self) -> Result<(), Hc12Error> {
+        let Some(delimiter) = self.delimiter else {
+            return Ok(self.wait_for_data().await?);
+        };
+
+        while self.rx_buffer.position(&[delimiter]).is_none() {
+            if self.rx_buffer.is_full() {
+                return Err(Hc12Error::BufferFull);
+            }
+            self.fill_rx_buffer().await?;
         }
 
         Ok(())
     }
 
-    pub async fn write_async(&mut self, data: &[u8]) -> Result<usize, esp_hal::uart::Error> {
-        self.uart.write_async(data).await
+    /// Waits for received bytes and appends as many as fit to the RX buffer.
+    pub(crate) async fn fill_rx_buffer(&mut self) -> Result<(), esp_hal::uart::Error> {
+        let mut buffer = [0u8; RX_BUFFER_LEN];
+        let free = self.rx_buffer.free();
+        let bytes_read = self.uart.read_async(&mut buffer[..free]).await?;
+        self.note_rx(bytes_read);
+        self.rx_buffer.push_slice(&buffer[..bytes_read]);
+
+        Ok(())
     }
 
-    pub async fn flush_async(&mut self) -> Result<(), esp_hal::uart::Error> {
-        self.uart.flush_async().await
+    /// Writes `data` in chunks of `chunk` bytes, flushing and waiting `gap_ms` after each chunk so
+    /// the module can send it over the air before the next one arrives.
+    pub async fn write_paced(
+        &mut self,
+        data: &[u8],
+        chunk: usize,
+        gap_ms: u32,
+    ) -> Result<(), Hc12Error> {
+        for data in data.chunks(chunk.max(1)) {
+            self.write_all_async(data).await?;
+            self.uart.flush_async().await?;
+            Timer::after_millis(u64::from(gap_ms)).await;
+        }
+
+        Ok(())
     }
 
-    pub async fn read_async(&mut self, buffer: &mut [u8]) -> Result<usize, esp_hal::uart::Error> {
-        self.uart.read_async(buffer).await
+    /// [`Hc12::write_paced`] in chunks of [`Hc12::max_chunk_bytes`], each followed by its air
+    /// time at the current mode and baud rate or the mode's minimum packet spacing, whichever is
+    /// longer, like [`Hc12::send_chunked`].
+    pub async fn write_paced_default(&mut self, data: &[u8]) -> Result<(), Hc12Error> {
+        let chunk = self.max_chunk_bytes();
+        let gap_ms = self.burst_gap_ms(chunk);
+
+        self.write_paced(data, chunk, gap_ms).await
+    }
+
+    /// Writes `data` in bursts of [`TransmissionMode::max_burst`] bytes for the current mode,
+    /// waiting after each burst until the module is ready for the next one.
+    pub async fn send_chunked(&mut self, data: &[u8]) -> Result<(), Hc12Error> {
+        let chunk = self.state.transmission_mode.max_burst();
+        let gap_ms = self.burst_gap_ms(chunk);
+
+        self.write_paced(data, chunk, gap_ms).await
+    }
+
+    /// Sends `data` like [`Hc12::send_chunked`], but only if the channel looks free: nothing has
+    /// been received but not yet read and the last byte arrived at least
+    /// [`Hc12::min_rx_idle_ms`] ago. Otherwise nothing is sent and [`Hc12Error::Busy`] is
+    /// returned, so the caller can back off instead of colliding with the peer.
+    pub async fn try_send(&mut self, data: &[u8]) -> Result<(), Hc12Error> {
+        if self.channel_busy()? {
+            return Err(Hc12Error::Busy);
+        }
+
+        self.send_chunked(data).await
+    }
+
+    pub async fn send_line(&mut self, line: &str) -> Result<(), Hc12Error> {
+        let terminator: &[u8] = (&self.line_terminator).into();
+
+        self.write_all_async(line.as_bytes()).await?;
+        self.write_all_async(terminator).await?;
+        self.uart.flush_async().await?;
+
+        Ok(())
+    }
+
+    /// Reads into `buffer` until the configured line terminator is received and returns the line
+    /// without the terminator. Fails with [`Hc12Error::Timeout`] if no complete line arrives within
+    /// `timeout`, the partial line is kept for the next call. Lines that don't fit into `buffer` or
+    /// [`RX_BUFFER_LEN`] are dropped with [`Hc12Error::BufferFull`].
+    pub async fn receive_line<'b>(
+        &mut self,
+        buffer: &'b mut [u8],
+        timeout: Duration,
+    ) -> Result<&'b str, Hc12Error> {
+        let terminator: &[u8] = (&self.line_terminator).into();
+        let deadline = Instant::now() + timeout;
+
+        // The line is collected in the RX buffer, so a timeout or a cancelled call leaves the
+        // partial line for the next read.
+        loop {
+            if let Some(len) = self.rx_buffer.pop_line(terminator, buffer) {
+                let len = len.map_err(|_| Hc12Error::BufferFull)?;
+                return core::str::from_utf8(&buffer[..len])
+                    .map_err(|_| Hc12Error::InvalidResponse);
+            }
+
+            if self.rx_buffer.is_full() || self.rx_buffer.len() >= buffer.len() + terminator.len() {
+                self.rx_buffer.clear();
+                return Err(Hc12Error::BufferFull);
+            }
+
+            with_deadline(deadline, self.fill_rx_buffer())
+                .await
+                .map_err(|_| Hc12Error::Timeout)??;
+        }
     }
 }
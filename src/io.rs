@@ -0,0 +1,73 @@
+//! `embedded-io` and `embedded-hal-nb` traits, so existing protocol code and parsers can run on
+//! top of the driver.
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+#[cfg(feature = "async")]
+use esp_hal::Async;
+use esp_hal::{Blocking, DriverMode};
+
+use crate::Hc12;
+
+impl<Dm: DriverMode, D, S: OutputPin> embedded_io::ErrorType for Hc12<'_, Dm, D, S> {
+    type Error = esp_hal::uart::Error;
+}
+
+/// `fill_buf` spins until at least one byte has been received. The returned slice ends where the RX
+/// buffer wraps around, the bytes after it follow once those before are consumed.
+impl<D: DelayNs, S: OutputPin> embedded_io::BufRead for Hc12<'_, Blocking, D, S> {
+    fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        while self.rx_available()? == 0 {}
+
+        Ok(self.rx_buffer.contiguous())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.rx_buffer.discard(amt);
+    }
+}
+
+/// `fill_buf` waits until at least one byte has been received and is cancellation safe. The
+/// returned slice ends where the RX buffer wraps around, the bytes after it follow once those
+/// before are consumed.
+#[cfg(feature = "async")]
+impl<D, S: OutputPin> embedded_io_async::BufRead for Hc12<'_, Async, D, S> {
+    async fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        if self.rx_buffer.is_empty() {
+            self.fill_rx_buffer().await?;
+        }
+
+        Ok(self.rx_buffer.contiguous())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.rx_buffer.discard(amt);
+    }
+}
+
+impl<Dm: DriverMode, D, S: OutputPin> embedded_hal_nb::serial::ErrorType for Hc12<'_, Dm, D, S> {
+    type Error = esp_hal::uart::Error;
+}
+
+/// Reads bytes from the RX buffer first, then from the UART FIFO, and returns `WouldBlock` once
+/// both are empty.
+impl<D, S: OutputPin> embedded_hal_nb::serial::Read for Hc12<'_, Blocking, D, S> {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        let mut byte = [0u8];
+        match self.try_read(&mut byte)? {
+            0 => Err(nb::Error::WouldBlock),
+            _ => Ok(byte[0]),
+        }
+    }
+}
+
+/// Writes bypass the duty-cycle governor, like [`Hc12::write`].
+impl<D, S: OutputPin> embedded_hal_nb::serial::Write for Hc12<'_, Blocking, D, S> {
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        embedded_hal_nb::serial::Write::write(&mut self.uart, word)
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        embedded_hal_nb::serial::Write::flush(&mut self.uart)
+    }
+}
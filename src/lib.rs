@@ -1,4 +1,67 @@
-#![no_std]
+#![cfg_attr(not(test), no_std)]
+#[macro_use]
+mod fmt;
+
+#[cfg(feature = "esp32c3")]
+mod addressed;
+#[cfg(feature = "esp32c3")]
+mod builder;
+mod command;
+#[cfg(feature = "esp32c3")]
+mod commands;
+#[cfg(feature = "esp32c3")]
+mod duty_cycle;
+mod error;
+#[cfg(feature = "esp32c3")]
+mod fragment;
+mod frame_reader;
+mod framed;
+mod generic;
+#[cfg(feature = "esp32c3")]
 mod hc_12;
+#[cfg(feature = "esp32c3")]
+mod io;
+#[cfg(all(feature = "esp32c3", feature = "async"))]
+mod ping;
+#[cfg(feature = "esp32c3")]
+mod poll;
+#[cfg(feature = "esp32c3")]
+mod power;
+pub mod protocol;
+#[cfg(all(feature = "esp32c3", feature = "async"))]
+mod pump;
+mod ring_buffer;
+mod settings;
+#[cfg(all(feature = "esp32c3", feature = "async"))]
+mod shared;
+#[cfg(feature = "esp32c3")]
+mod supervisor;
 
+#[cfg(feature = "esp32c3")]
+pub use addressed::*;
+#[cfg(feature = "esp32c3")]
+pub use builder::*;
+pub use command::*;
+#[cfg(feature = "esp32c3")]
+pub use commands::*;
+#[cfg(feature = "esp32c3")]
+pub use duty_cycle::*;
+pub use error::*;
+#[cfg(feature = "esp32c3")]
+pub use fragment::*;
+pub use frame_reader::*;
+pub use framed::*;
+pub use generic::*;
+#[cfg(feature = "esp32c3")]
 pub use hc_12::*;
+#[cfg(all(feature = "esp32c3", feature = "async"))]
+pub use ping::*;
+#[cfg(feature = "esp32c3")]
+pub use power::*;
+#[cfg(all(feature = "esp32c3", feature = "async"))]
+pub use pump::*;
+pub use settings::*;
+#[cfg(all(feature = "esp32c3", feature = "async"))]
+pub use shared::*;
+#[cfg(feature = "esp32c3")]
+pub use supervisor::*;
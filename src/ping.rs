@@ -0,0 +1,103 @@
+use core::convert::Infallible;
+
+use embassy_time::{with_timeout, Duration, Instant, Timer};
+use embedded_hal::digital::OutputPin;
+use esp_hal::Async;
+
+use crate::{FrameCodec, FrameInfo, Hc12Error, Hc12Framed, BROADCAST_ADDRESS};
+
+/// First payload byte of a ping sent by [`Hc12Framed::ping`].
+pub const PING_REQUEST: u8 = 0xF0;
+/// First payload byte of the echo to a ping.
+pub const PING_REPLY: u8 = 0xF1;
+
+impl<C: FrameCodec, const N: usize, D, S: OutputPin> Hc12Framed<'_, Async, C, N, D, S> {
+    /// Checks the link end to end: sends a `[PING_REQUEST, token]` frame to all nodes and waits up
+    /// to `timeout` for a peer running [`Hc12Framed::respond_to_pings`] to echo it, returning the
+    /// round-trip time. Unlike the AT `test`, which only reaches the local module, this needs a
+    /// peer in range. Other frames received while waiting are dropped.
+    pub async fn ping(&mut self, token: u8, timeout: Duration) -> Result<Duration, Hc12Error> {
+        let start = Instant::now();
+        self.send_frame(&[PING_REQUEST, token]).await?;
+
+        with_timeout(timeout, async {
+            let mut buffer = [0u8; 2];
+            loop {
+                match self.recv_frame(&mut buffer).await {
+                    Ok(info) if buffer[..info.len] == [PING_REPLY, token] => return Ok(()),
+                    Err(error @ Hc12Error::UartError(_)) => return Err(error),
+                    _ => {}
+                }
+            }
+        })
+        .await
+        .map_err(|_| Hc12Error::Timeout)??;
+
+        Ok(start.elapsed())
+    }
+
+    /// Echoes `payload` back to its sender if it is a ping, for receive loops that also handle
+    /// other frames. Returns whether it was one.
+    pub async fn reply_to_ping(
+        &mut self,
+        payload: &[u8],
+        info: &FrameInfo,
+    ) -> Result<bool, Hc12Error> {
+        let [PING_REQUEST, token] = *payload else {
+            return Ok(false);
+        };
+
+        let destination = info.source.unwrap_or(BROADCAST_ADDRESS);
+        self.send_frame_to(destination, &[PING_REPLY, token])
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Sends a ping as heartbeat every `period`, e.g. to keep the link in FU2 active, and logs a
+    /// warning for every period that passes without an answer. Only UART errors end it.
+    ///
+    /// It can be cancelled at any point, e.g. by a `select` against the application's own traffic
+    /// that takes the driver over when there is data to send. Frames received while it runs are
+    /// dropped, and an answer arriving after a cancellation reaches the next reader as a
+    /// `[PING_REPLY, token]` frame to ignore.
+    pub async fn keepalive(&mut self, period: Duration) -> Result<Infallible, Hc12Error> {
+        let mut token = 0u8;
+        let mut missed = 0u32;
+
+        loop {
+            let next = Instant::now() + period;
+            match self.ping(token, period).await {
+                Ok(round_trip) => {
+                    trace!("keepalive answered in {} ms", round_trip.as_millis());
+                    missed = 0;
+                }
+                Err(error @ Hc12Error::UartError(_)) => return Err(error),
+                Err(_) => {
+                    missed += 1;
+                    warn!(
+                        "keepalive: no answer from the peer, {} missed in a row",
+                        missed
+                    );
+                }
+            }
+            token = token.wrapping_add(1);
+            Timer::at(next).await;
+        }
+    }
+
+    /// Answers pings of [`Hc12Framed::ping`] forever, dropping all other frames. Only UART errors
+    /// end it. Like [`Hc12Framed::pump_frames`] this is meant to run inside a task of your own.
+    pub async fn respond_to_pings(&mut self) -> Result<Infallible, Hc12Error> {
+        let mut buffer = [0u8; 2];
+
+        loop {
+            let info = match self.recv_frame(&mut buffer).await {
+                Ok(info) => info,
+                Err(error @ Hc12Error::UartError(_)) => return Err(error),
+                Err(_) => continue,
+            };
+            self.reply_to_ping(&buffer[..info.len], &info).await?;
+        }
+    }
+}
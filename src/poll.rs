@@ -0,0 +1,278 @@
+use embedded_hal::digital::OutputPin;
+use esp_hal::Blocking;
+use heapless::Vec;
+
+use crate::command::{self, CommandTransport, RESPONSE_LEN};
+use crate::hc_12::BAUD_SWITCH_SETTLE_MS;
+use crate::{BaudRate, Channel, Hc12, Hc12Error, TransmissionMode, TransmitPower};
+
+/// Time in milliseconds each step of a polled command waits, like the blocking commands do.
+const STEP_MS: u64 = 200;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Step {
+    EnteringCommandMode,
+    AwaitingResponse,
+    LeavingCommandMode,
+    /// `AT+B` was confirmed, the host UART still has to follow once TX is flushed.
+    SwitchingBaud(BaudRate),
+    /// The host UART follows, waiting for the module to switch as well.
+    SettlingBaud(BaudRate),
+}
+
+/// A command in progress through the `poll_*` methods.
+pub(crate) struct PendingCommand {
+    command: Vec<u8, RESPONSE_LEN>,
+    response: Vec<u8, RESPONSE_LEN>,
+    step: Step,
+    since_ms: u64,
+}
+
+fn now_ms() -> u64 {
+    esp_hal::time::now().duration_since_epoch().to_millis()
+}
+
+/// Drives the command layer through [`Hc12::poll_exchange`], remembering whether the exchange is
+/// still in progress.
+struct PollTransport<'a, 'd, D, S: OutputPin> {
+    hc12: &'a mut Hc12<'d, Blocking, D, S>,
+    would_block: bool,
+}
+
+impl<D, S: OutputPin> CommandTransport for PollTransport<'_, '_, D, S> {
+    fn exchange(&mut self, command: &[u8], response: &mut [u8]) -> Result<usize, Hc12Error> {
+        self.hc12
+            .poll_exchange(command, response)
+            .map_err(|error| match error {
+                nb::Error::WouldBlock => {
+                    self.would_block = true;
+                    // Never surfaces, the caller reports `WouldBlock` instead.
+                    Hc12Error::Timeout
+                }
+                nb::Error::Other(error) => error,
+            })
+    }
+}
+
+impl<D, S: OutputPin> Hc12<'_, Blocking, D, S> {
+    /// Advances the exchange of `command` by one step if its wait has elapsed. A different command
+    /// can only be started once the pending one has completed.
+    fn poll_exchange(
+        &mut self,
+        command: &[u8],
+        response: &mut [u8],
+    ) -> nb::Result<usize, Hc12Error> {
+        let now_ms = now_ms();
+
+        let Some(pending) = self.pending_command.as_mut() else {
+            self.require_set_pin().map_err(nb::Error::Other)?;
+            // Data still in the TX FIFO would be taken for a command once SET switches.
+            embedded_hal_nb::serial::Write::flush(&mut self.uart)
+                .map_err(|error| error.map(Hc12Error::from))?;
+            self.discard_rx();
+
+            self.drive_set(self.set.polarity.command_level())
+                .map_err(nb::Error::Other)?;
+            self.pending_command = Some(PendingCommand {
+                command: Vec::from_slice(command).map_err(|_| Hc12Error::Config)?,
+                response: Vec::new(),
+                step: Step::EnteringCommandMode,
+                since_ms: now_ms,
+            });
+            return Err(nb::Error::WouldBlock);
+        };
+
+        if pending.command != command {
+            return Err(nb::Error::Other(Hc12Error::Busy));
+        }
+        if now_ms - pending.since_ms < STEP_MS {
+            return Err(nb::Error::WouldBlock);
+        }
+        pending.since_ms = now_ms;
+
+        match pending.step {
+            Step::EnteringCommandMode => {
+                trace!("sending command {:?}", command);
+                if let Err(error) = self.uart.write_bytes(command) {
+                    self.pending_command = None;
+                    let _ = self.drive_set(self.set.polarity.transparent_level());
+                    return Err(nb::Error::Other(error.into()));
+                }
+                pending.step = Step::AwaitingResponse;
+            }
+            Step::AwaitingResponse => {
+                let mut buffer = [0u8; RESPONSE_LEN];
+                let result = self.uart.read_buffered_bytes(&mut buffer);
+
+                let bytes_read = match result {
+                    Ok(bytes_read) => bytes_read,
+                    Err(error) => {
+                        self.pending_command = None;
+                        let _ = self.drive_set(self.set.polarity.transparent_level());
+                        return Err(nb::Error::Other(error.into()));
+                    }
+                };
+                trace!("received response {:?}", &buffer[..bytes_read]);
+                pending.response = Vec::from_slice(&buffer[..bytes_read]).unwrap();
+                pending.step = Step::LeavingCommandMode;
+                if let Err(error) = self.drive_set(self.set.polarity.transparent_level()) {
+                    self.pending_command = None;
+                    return Err(nb::Error::Other(error));
+                }
+            }
+            Step::LeavingCommandMode => {
+                let pending = self.pending_command.take().unwrap();
+                let len = pending.response.len().min(response.len());
+                response[..len].copy_from_slice(&pending.response[..len]);
+                self.store_response(&pending.response);
+
+                return Ok(len);
+            }
+            Step::SwitchingBaud(_) | Step::SettlingBaud(_) => {
+                return Err(nb::Error::Other(Hc12Error::Busy));
+            }
+        }
+
+        Err(nb::Error::WouldBlock)
+    }
+
+    /// Runs `f` on the command layer, returning `WouldBlock` while its exchange is in progress.
+    fn poll_with<T>(
+        &mut self,
+        f: impl FnOnce(&mut PollTransport<'_, '_, D, S>) -> Result<T, Hc12Error>,
+    ) -> nb::Result<T, Hc12Error> {
+        let mut transport = PollTransport {
+            hc12: self,
+            would_block: false,
+        };
+        let result = f(&mut transport);
+        if transport.would_block {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        result.map_err(nb::Error::Other)
+    }
+
+    /// Whether a `poll_*` command is in progress. The blocking commands fail with
+    /// [`Hc12Error::Busy`] until it has completed.
+    pub fn command_pending(&self) -> bool {
+        self.pending_command.is_some()
+    }
+
+    /// Fails with [`Hc12Error::Busy`] while a `poll_*` command holds SET.
+    pub(crate) fn require_no_pending_command(&self) -> Result<(), Hc12Error> {
+        if self.command_pending() {
+            return Err(Hc12Error::Busy);
+        }
+
+        Ok(())
+    }
+
+    /// Non-blocking [`Hc12::test`]: call repeatedly until it returns something other than
+    /// `WouldBlock`. Each call only advances the SET/write/read sequence when the current wait has
+    /// elapsed. Until the command completes, other `poll_*` commands fail with
+    /// [`Hc12Error::Busy`]. The same holds for all `poll_*` methods.
+    pub fn poll_test(&mut self) -> nb::Result<(), Hc12Error> {
+        self.poll_with(|transport| command::test(transport))
+    }
+
+    /// Non-blocking [`Hc12::set_baud`]. After the module confirmed the new rate, the command stays
+    /// pending while TX is flushed, the host UART follows and the module settles.
+    pub fn poll_set_baud(&mut self, baud_rate: &BaudRate) -> nb::Result<(), Hc12Error> {
+        let switching = self.pending_command.as_ref().is_some_and(|pending| {
+            matches!(pending.step, Step::SwitchingBaud(_) | Step::SettlingBaud(_))
+        });
+        if !switching {
+            self.poll_with(|transport| command::set_baud(transport, baud_rate))?;
+            self.pending_command = Some(PendingCommand {
+                command: Vec::new(),
+                response: Vec::new(),
+                step: Step::SwitchingBaud(*baud_rate),
+                since_ms: now_ms(),
+            });
+        }
+
+        self.poll_baud_switch(baud_rate)
+    }
+
+    /// Advances the host side of [`Hc12::poll_set_baud`] once the module confirmed the new rate.
+    fn poll_baud_switch(&mut self, baud_rate: &BaudRate) -> nb::Result<(), Hc12Error> {
+        let now_ms = now_ms();
+        let Some(pending) = self.pending_command.as_ref() else {
+            return Err(nb::Error::Other(Hc12Error::Busy));
+        };
+
+        match pending.step {
+            Step::SwitchingBaud(target) if target == *baud_rate => {
+                // Bytes still in the TX FIFO would go out at the new rate once the UART is
+                // reconfigured.
+                match embedded_hal_nb::serial::Write::flush(&mut self.uart) {
+                    Ok(()) => {}
+                    Err(nb::Error::WouldBlock) => return Err(nb::Error::WouldBlock),
+                    Err(nb::Error::Other(error)) => {
+                        self.pending_command = None;
+                        return Err(nb::Error::Other(error.into()));
+                    }
+                }
+                if self.set_host_baud(target).is_err() {
+                    self.pending_command = None;
+                    return Err(nb::Error::Other(Hc12Error::TransmissionMode));
+                }
+
+                if let Some(pending) = self.pending_command.as_mut() {
+                    pending.step = Step::SettlingBaud(target);
+                    pending.since_ms = now_ms;
+                }
+                Err(nb::Error::WouldBlock)
+            }
+            Step::SettlingBaud(target) if target == *baud_rate => {
+                if now_ms - pending.since_ms < u64::from(BAUD_SWITCH_SETTLE_MS) {
+                    return Err(nb::Error::WouldBlock);
+                }
+                self.pending_command = None;
+
+                Ok(())
+            }
+            _ => Err(nb::Error::Other(Hc12Error::Busy)),
+        }
+    }
+
+    /// Non-blocking [`Hc12::set_transmission_mode`].
+    pub fn poll_set_transmission_mode(
+        &mut self,
+        transmission_mode: &TransmissionMode,
+    ) -> nb::Result<(), Hc12Error> {
+        let new_baud_rate = self
+            .poll_with(|transport| command::set_transmission_mode(transport, transmission_mode))?;
+        self.apply_transmission_mode(transmission_mode, new_baud_rate)
+            .map_err(nb::Error::Other)
+    }
+
+    /// Non-blocking [`Hc12::set_channel`].
+    pub fn poll_set_channel(&mut self, channel: Channel) -> nb::Result<(), Hc12Error> {
+        self.validate_channel(channel)?;
+
+        self.poll_with(|transport| command::set_channel(transport, channel))?;
+        self.state.channel = channel;
+
+        Ok(())
+    }
+
+    /// Non-blocking [`Hc12::set_transmit_power`].
+    pub fn poll_set_transmit_power(
+        &mut self,
+        transmit_power: &TransmitPower,
+    ) -> nb::Result<(), Hc12Error> {
+        self.poll_with(|transport| command::set_transmit_power(transport, transmit_power))?;
+        self.state.transmit_power = *transmit_power;
+
+        Ok(())
+    }
+
+    /// Non-blocking [`Hc12::set_default`].
+    pub fn poll_set_default(&mut self) -> nb::Result<(), Hc12Error> {
+        self.poll_with(|transport| command::set_default(transport))?;
+
+        Ok(self.reset_state()?)
+    }
+}
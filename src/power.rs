@@ -0,0 +1,193 @@
+#[cfg(feature = "async")]
+use embassy_time::Timer;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{OutputPin, PinState};
+#[cfg(feature = "async")]
+use esp_hal::Async;
+use esp_hal::{delay::Delay, gpio::OutputOpenDrain, Blocking, DriverMode};
+
+use crate::settings::drive_pin;
+use crate::{Hc12, Hc12Config, Hc12Error};
+
+/// Time the module needs after power is applied before it reacts to SET.
+pub const POWER_UP_MS: u32 = 200;
+
+/// Time the supply is kept off by [`PoweredHc12::power_cycle`], long enough for the module's
+/// decoupling capacitors to discharge.
+pub const POWER_OFF_MS: u32 = 100;
+
+/// An [`Hc12`] whose supply is switched through `power`, e.g. the gate of a high-side FET, for
+/// zero standby current and to recover a wedged module with a hard reset.
+pub struct PoweredHc12<'d, Dm: DriverMode, P, D = Delay, S: OutputPin = OutputOpenDrain<'d>> {
+    hc12: Hc12<'d, Dm, D, S>,
+    power: P,
+    on_level: PinState,
+    restore_config: Option<Hc12Config>,
+}
+
+impl<'d, Dm: DriverMode, P: OutputPin, D, S: OutputPin> PoweredHc12<'d, Dm, P, D, S> {
+    /// Wraps a driver for a module that is currently powered. `on_level` is the level of `power`
+    /// that switches the supply on, low for a P-channel high-side FET.
+    pub fn new(
+        hc12: Hc12<'d, Dm, D, S>,
+        mut power: P,
+        on_level: PinState,
+    ) -> Result<Self, Hc12Error> {
+        drive_pin(&mut power, on_level)?;
+
+        Ok(Self {
+            hc12,
+            power,
+            on_level,
+            restore_config: None,
+        })
+    }
+
+    /// Configuration applied with `bring_up` after every power-up, `None` to keep whatever the
+    /// module stored.
+    pub fn set_restore_config(&mut self, config: Option<Hc12Config>) {
+        self.restore_config = config;
+    }
+
+    pub fn inner(&mut self) -> &mut Hc12<'d, Dm, D, S> {
+        &mut self.hc12
+    }
+
+    pub fn into_parts(self) -> (Hc12<'d, Dm, D, S>, P) {
+        (self.hc12, self.power)
+    }
+
+    /// Drives SET low so it doesn't feed the unpowered module through its pins, then cuts power.
+    fn cut_power(&mut self) -> Result<(), Hc12Error> {
+        let off_level = !self.on_level;
+
+        self.hc12.drive_set(PinState::Low)?;
+        // Low is the command level for an active-low SET, but an unpowered module isn't in
+        // command mode, and driving SET high on drop would feed it.
+        self.hc12.set.command_mode = false;
+        drive_pin(&mut self.power, off_level)
+    }
+}
+
+impl<D: DelayNs, P: OutputPin, S: OutputPin> PoweredHc12<'_, Blocking, P, D, S> {
+    /// Sends what is still queued and switches the module off.
+    pub fn power_down(&mut self) -> Result<(), Hc12Error> {
+        self.hc12.flush()?;
+        self.cut_power()
+    }
+
+    /// Switches the module on, waits for it to start and pulses SET as on power-on, then applies
+    /// the restore configuration, if any.
+    pub fn power_up(&mut self) -> Result<(), Hc12Error> {
+        let transparent_level = self.hc12.set.polarity.transparent_level();
+        let command_level = self.hc12.set.polarity.command_level();
+
+        drive_pin(&mut self.power, self.on_level)?;
+        self.hc12.drive_set(transparent_level)?;
+        self.hc12.delay.delay_ms(POWER_UP_MS);
+        self.hc12.drive_set(command_level)?;
+        self.hc12.delay.delay_ms(200);
+        self.hc12.drive_set(transparent_level)?;
+        self.hc12.delay.delay_ms(200);
+
+        if let Some(config) = self.restore_config {
+            self.hc12.bring_up(&config)?;
+        }
+
+        Ok(())
+    }
+
+    /// Hard reset: [`PoweredHc12::power_down`], [`POWER_OFF_MS`] without power, then
+    /// [`PoweredHc12::power_up`].
+    pub fn power_cycle(&mut self) -> Result<(), Hc12Error> {
+        self.power_down()?;
+        self.hc12.delay.delay_ms(POWER_OFF_MS);
+        self.power_up()
+    }
+
+    /// Rescues a module whose baud rate or format is unknown, e.g. after [`Hc12::auto_baud`]
+    /// failed: powers it up with SET held in command mode, which makes it listen for AT commands
+    /// at 9600 baud whatever it stored, restores the factory settings with `AT+DEFAULT` and then
+    /// applies the restore configuration, if any.
+    pub fn recover(&mut self) -> Result<(), Hc12Error> {
+        self.hc12.require_set_pin()?;
+        self.power_down()?;
+        self.hc12.delay.delay_ms(POWER_OFF_MS);
+
+        let command_level = self.hc12.set.polarity.command_level();
+        self.hc12.drive_set(command_level)?;
+        drive_pin(&mut self.power, self.on_level)?;
+        self.hc12.delay.delay_ms(POWER_UP_MS);
+
+        self.hc12.reset_state()?;
+        self.hc12.set_default()?;
+
+        if let Some(config) = self.restore_config {
+            self.hc12.bring_up(&config)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<D, P: OutputPin, S: OutputPin> PoweredHc12<'_, Async, P, D, S> {
+    /// Sends what is still queued and switches the module off.
+    pub async fn power_down(&mut self) -> Result<(), Hc12Error> {
+        self.hc12.flush_async().await?;
+        self.cut_power()
+    }
+
+    /// Switches the module on, waits for it to start and pulses SET as on power-on, then applies
+    /// the restore configuration, if any.
+    pub async fn power_up(&mut self) -> Result<(), Hc12Error> {
+        let transparent_level = self.hc12.set.polarity.transparent_level();
+        let command_level = self.hc12.set.polarity.command_level();
+
+        drive_pin(&mut self.power, self.on_level)?;
+        self.hc12.drive_set(transparent_level)?;
+        Timer::after_millis(POWER_UP_MS.into()).await;
+        self.hc12.drive_set(command_level)?;
+        Timer::after_millis(200).await;
+        self.hc12.drive_set(transparent_level)?;
+        Timer::after_millis(200).await;
+
+        if let Some(config) = self.restore_config {
+            self.hc12.bring_up(&config).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Hard reset: [`PoweredHc12::power_down`], [`POWER_OFF_MS`] without power, then
+    /// [`PoweredHc12::power_up`].
+    pub async fn power_cycle(&mut self) -> Result<(), Hc12Error> {
+        self.power_down().await?;
+        Timer::after_millis(POWER_OFF_MS.into()).await;
+        self.power_up().await
+    }
+
+    /// Rescues a module whose baud rate or format is unknown, e.g. after [`Hc12::auto_baud`]
+    /// failed: powers it up with SET held in command mode, which makes it listen for AT commands
+    /// at 9600 baud whatever it stored, restores the factory settings with `AT+DEFAULT` and then
+    /// applies the restore configuration, if any.
+    pub async fn recover(&mut self) -> Result<(), Hc12Error> {
+        self.hc12.require_set_pin()?;
+        self.power_down().await?;
+        Timer::after_millis(POWER_OFF_MS.into()).await;
+
+        let command_level = self.hc12.set.polarity.command_level();
+        self.hc12.drive_set(command_level)?;
+        drive_pin(&mut self.power, self.on_level)?;
+        Timer::after_millis(POWER_UP_MS.into()).await;
+
+        self.hc12.reset_state()?;
+        self.hc12.set_default().await?;
+
+        if let Some(config) = self.restore_config {
+            self.hc12.bring_up(&config).await?;
+        }
+
+        Ok(())
+    }
+}
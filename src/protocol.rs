@@ -0,0 +1,550 @@
+//! The AT protocol without any I/O: formatting of commands and parsing of the module's responses.
+//! Responses are taken as received, with or without their line ending.
+
+use core::fmt::{Display, Write};
+
+use heapless::String;
+
+use crate::{
+    BaudRate, Channel, Hc12Error, Hc12Params, PowerLevel, TransmissionMode, TransmitPower,
+};
+
+/// Longest command or single-line response of the module.
+pub const COMMAND_LEN: usize = 14;
+
+pub const TEST_COMMAND: &str = "AT";
+pub const DEFAULT_COMMAND: &str = "AT+DEFAULT";
+pub const GET_CHANNEL_COMMAND: &str = "AT+RC";
+pub const GET_PARAMS_COMMAND: &str = "AT+RX";
+pub const SLEEP_COMMAND: &str = "AT+SLEEP";
+
+/// Builds an `AT+<name><args>` command, failing with [`Hc12Error::Config`] instead of panicking
+/// if it grows beyond [`COMMAND_LEN`].
+pub(crate) struct AtCommand(String<COMMAND_LEN>);
+
+impl AtCommand {
+    pub(crate) fn new(name: &str) -> Result<Self, Hc12Error> {
+        let mut command = String::new();
+        command.push_str("AT+").map_err(|_| Hc12Error::Config)?;
+        command.push_str(name).map_err(|_| Hc12Error::Config)?;
+
+        Ok(Self(command))
+    }
+
+    pub(crate) fn arg(mut self, arg: impl Display) -> Result<Self, Hc12Error> {
+        write!(self.0, "{}", arg).map_err(|_| Hc12Error::Config)?;
+
+        Ok(self)
+    }
+
+    pub(crate) fn finish(self) -> String<COMMAND_LEN> {
+        self.0
+    }
+}
+
+/// Response starting with `ERROR`, with which the module refuses a command it understood, e.g. one
+/// its firmware doesn't support. Holds the first [`COMMAND_LEN`] bytes of the response.
+#[derive(Clone, Copy, PartialEq)]
+pub struct RejectedResponse {
+    bytes: [u8; COMMAND_LEN],
+    len: usize,
+}
+
+impl RejectedResponse {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl core::fmt::Debug for RejectedResponse {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match core::str::from_utf8(self.as_bytes()) {
+            Ok(response) => write!(f, "{:?}", response),
+            Err(_) => write!(f, "{:?}", self.as_bytes()),
+        }
+    }
+}
+
+/// Cuts `response` down to the module's answer: bytes before the first `OK` or `ERROR`, e.g. line
+/// noise picked up while SET switched, and the trailing CR/LF are dropped. Works on raw bytes, so
+/// noise that isn't UTF-8 doesn't spoil an otherwise valid answer.
+pub fn trim_response(response: &[u8]) -> &[u8] {
+    let start = (0..response.len())
+        .find(|&index| {
+            response[index..].starts_with(b"OK") || response[index..].starts_with(b"ERROR")
+        })
+        .unwrap_or(0);
+    let end = response
+        .iter()
+        .rposition(|&byte| byte != b'\r' && byte != b'\n')
+        .map_or(start, |last| last + 1);
+
+    &response[start..end]
+}
+
+/// Fails with [`Hc12Error::ModuleRejected`] if `response` is an `ERROR`.
+pub fn check_rejected(response: &[u8]) -> Result<(), Hc12Error> {
+    let response = trim_response(response);
+    if !response.starts_with(b"ERROR") {
+        return Ok(());
+    }
+
+    let len = response.len().min(COMMAND_LEN);
+    let mut bytes = [0u8; COMMAND_LEN];
+    bytes[..len].copy_from_slice(&response[..len]);
+
+    Err(Hc12Error::ModuleRejected(RejectedResponse { bytes, len }))
+}
+
+/// Decodes a response trimmed with [`trim_response`]. Some clones end responses with a bare `\r`
+/// or nothing at all instead of `\r\n`, so the trailing CR/LF is not compared.
+pub fn normalize_response(response: &[u8]) -> Result<String<COMMAND_LEN>, Hc12Error> {
+    let response =
+        core::str::from_utf8(trim_response(response)).map_err(|_| Hc12Error::InvalidResponse)?;
+    let mut result = String::new();
+    result
+        .push_str(response)
+        .map_err(|_| Hc12Error::InvalidResponse)?;
+
+    Ok(result)
+}
+
+/// Checks the `OK` the module answers [`TEST_COMMAND`] with.
+pub fn parse_test_response(response: &[u8]) -> Result<(), Hc12Error> {
+    check_rejected(response)?;
+    match trim_response(response) {
+        b"OK" => Ok(()),
+        _ => Err(Hc12Error::Test),
+    }
+}
+
+pub fn format_set_baud(baud_rate: &BaudRate) -> Result<String<COMMAND_LEN>, Hc12Error> {
+    Ok(AtCommand::new("B")?.arg(u32::from(baud_rate))?.finish())
+}
+
+/// Parses the confirmation of a baud rate change, e.g. `OK+B9600`.
+pub fn parse_baud_response(response: &[u8]) -> Result<BaudRate, Hc12Error> {
+    check_rejected(response)?;
+    let response = normalize_response(response).map_err(|_| Hc12Error::BaudRate)?;
+    let baud_rate = response
+        .strip_prefix("OK+B")
+        .and_then(|baud_rate| str::parse::<u32>(baud_rate).ok())
+        .ok_or(Hc12Error::BaudRate)?;
+
+    BaudRate::try_from(baud_rate)
+}
+
+pub fn format_set_transmission_mode(
+    transmission_mode: &TransmissionMode,
+) -> Result<String<COMMAND_LEN>, Hc12Error> {
+    Ok(AtCommand::new("FU")?
+        .arg(u32::from(transmission_mode))?
+        .finish())
+}
+
+/// Parses the confirmation of a transmission mode change, e.g. `OK+FU3` or `OK+FU4,B1200`. Also
+/// returns the baud rate the module moved to, if the mode change forced one. That part is parsed
+/// on its own: if a firmware formats it unexpectedly, the mode is still confirmed and the inner
+/// result is [`Hc12Error::InvalidResponse`].
+pub fn parse_transmission_mode_response(
+    response: &[u8],
+) -> Result<(TransmissionMode, Result<Option<BaudRate>, Hc12Error>), Hc12Error> {
+    check_rejected(response)?;
+    let response = normalize_response(response).map_err(|_| Hc12Error::TransmissionMode)?;
+
+    let mut splitted = response.split(",");
+    let transmission_mode = splitted
+        .next()
+        .and_then(|result| result.strip_prefix("OK+FU"))
+        .and_then(|transmission_mode| transmission_mode.get(..1))
+        .and_then(|transmission_mode| str::parse::<u32>(transmission_mode).ok())
+        .ok_or(Hc12Error::TransmissionMode)?;
+    let transmission_mode = TransmissionMode::try_from(transmission_mode)?;
+
+    let baud_rate = splitted
+        .next()
+        .map(|new_baud_rate| {
+            let new_baud_rate = new_baud_rate
+                .trim()
+                .strip_prefix('B')
+                .ok_or(Hc12Error::InvalidResponse)?;
+            str::parse::<u32>(new_baud_rate)
+                .ok()
+                .and_then(|new_baud_rate| BaudRate::try_from(new_baud_rate).ok())
+                .ok_or(Hc12Error::InvalidResponse)
+        })
+        .transpose();
+
+    Ok((transmission_mode, baud_rate))
+}
+
+pub fn format_set_channel(channel: Channel) -> Result<String<COMMAND_LEN>, Hc12Error> {
+    Ok(AtCommand::new("C")?
+        .arg(format_args!("{:03}", channel.get()))?
+        .finish())
+}
+
+/// Parses the confirmation of a channel change, e.g. `OK+C001`.
+pub fn parse_channel_response(response: &[u8]) -> Result<Channel, Hc12Error> {
+    check_rejected(response)?;
+    let response = normalize_response(response).map_err(|_| Hc12Error::Channel)?;
+    let channel = response
+        .strip_prefix("OK+C")
+        .and_then(|channel| str::parse::<u8>(channel).ok())
+        .ok_or(Hc12Error::Channel)?;
+
+    Channel::try_from(channel)
+}
+
+pub fn format_set_transmit_power(
+    transmit_power: &TransmitPower,
+) -> Result<String<COMMAND_LEN>, Hc12Error> {
+    Ok(AtCommand::new("P")?
+        .arg(u32::from(transmit_power))?
+        .finish())
+}
+
+/// Parses the confirmation of a transmit power change, e.g. `OK+P8`.
+pub fn parse_transmit_power_response(response: &[u8]) -> Result<TransmitPower, Hc12Error> {
+    check_rejected(response)?;
+    let response = normalize_response(response).map_err(|_| Hc12Error::TransmitPower)?;
+
+    response
+        .strip_prefix("OK+P")
+        .and_then(|transmit_power| str::parse::<u8>(transmit_power).ok())
+        .and_then(PowerLevel::new)
+        .map(TransmitPower::from)
+        .ok_or(Hc12Error::TransmitPower)
+}
+
+/// Checks the `OK+DEFAULT` the module answers [`DEFAULT_COMMAND`] with.
+pub fn parse_default_response(response: &[u8]) -> Result<(), Hc12Error> {
+    check_rejected(response)?;
+    match trim_response(response) {
+        b"OK+DEFAULT" => Ok(()),
+        _ => Err(Hc12Error::Default),
+    }
+}
+
+pub fn parse_sleep_response(response: &[u8]) -> Result<(), Hc12Error> {
+    check_rejected(response)?;
+    match trim_response(response) {
+        b"OK+SLEEP" => Ok(()),
+        _ => Err(Hc12Error::Sleep),
+    }
+}
+
+/// Parses a channel report such as `OK+RC001`, the answer to [`GET_CHANNEL_COMMAND`].
+pub fn parse_get_channel_response(response: &[u8]) -> Result<Channel, Hc12Error> {
+    check_rejected(response)?;
+    let response = normalize_response(response)?;
+    let channel = response
+        .strip_prefix("OK+RC")
+        .and_then(|channel| str::parse::<u8>(channel).ok())
+        .ok_or(Hc12Error::InvalidResponse)?;
+
+    Channel::new(channel).ok_or(Hc12Error::InvalidResponse)
+}
+
+/// Parses the answer to [`GET_PARAMS_COMMAND`], one line per setting:
+/// `OK+B9600`, `OK+RC001`, `OK+RP:+20dBm` and `OK+FU3`. Lines that aren't UTF-8 are taken for
+/// noise and skipped; a setting whose line is missing still fails the parse.
+pub fn parse_params_response(response: &[u8]) -> Result<Hc12Params, Hc12Error> {
+    check_rejected(response)?;
+
+    let mut baud_rate = None;
+    let mut channel = None;
+    let mut transmission_mode = None;
+    let mut transmit_power = None;
+
+    let lines = response
+        .split(|&byte| byte == b'\r' || byte == b'\n')
+        .map(trim_response)
+        .filter_map(|line| core::str::from_utf8(line).ok())
+        .filter(|line| !line.is_empty());
+    for line in lines {
+        if let Some(value) = line.strip_prefix("OK+B") {
+            let value = str::parse::<u32>(value).map_err(|_| Hc12Error::InvalidResponse)?;
+            baud_rate = Some(BaudRate::try_from(value)?);
+        } else if line.starts_with("OK+RC") {
+            channel = Some(parse_get_channel_response(line.as_bytes())?);
+        } else if let Some(value) = line.strip_prefix("OK+FU") {
+            let value = str::parse::<u32>(value).map_err(|_| Hc12Error::InvalidResponse)?;
+            transmission_mode = Some(TransmissionMode::try_from(value)?);
+        } else if let Some(value) = line
+            .strip_prefix("OK+RP:")
+            .and_then(|value| value.strip_suffix("dBm"))
+        {
+            let dbm = str::parse::<i8>(value).map_err(|_| Hc12Error::InvalidResponse)?;
+            transmit_power = Some(
+                (1..=8)
+                    .filter_map(PowerLevel::new)
+                    .map(TransmitPower::from)
+                    .find(|transmit_power| transmit_power.dbm() == dbm)
+                    .ok_or(Hc12Error::InvalidResponse)?,
+            );
+        }
+    }
+
+    Ok(Hc12Params {
+        baud_rate: baud_rate.ok_or(Hc12Error::InvalidResponse)?,
+        channel: channel.ok_or(Hc12Error::InvalidResponse)?,
+        transmission_mode: transmission_mode.ok_or(Hc12Error::InvalidResponse)?,
+        transmit_power: transmit_power.ok_or(Hc12Error::InvalidResponse)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_set_baud_covers_all_baud_rates() {
+        let cases = [
+            (BaudRate::Baud1200, "AT+B1200"),
+            (BaudRate::Baud2400, "AT+B2400"),
+            (BaudRate::Baud4800, "AT+B4800"),
+            (BaudRate::Baud9600, "AT+B9600"),
+            (BaudRate::Baud19200, "AT+B19200"),
+            (BaudRate::Baud38400, "AT+B38400"),
+            (BaudRate::Baud57600, "AT+B57600"),
+            (BaudRate::Baud115200, "AT+B115200"),
+        ];
+
+        for (baud_rate, command) in cases {
+            assert_eq!(format_set_baud(&baud_rate).unwrap(), command);
+        }
+    }
+
+    #[test]
+    fn format_set_transmission_mode_covers_all_modes() {
+        let cases = [
+            (TransmissionMode::Fu1, "AT+FU1"),
+            (TransmissionMode::Fu2, "AT+FU2"),
+            (TransmissionMode::Fu3, "AT+FU3"),
+            (TransmissionMode::Fu4, "AT+FU4"),
+        ];
+
+        for (transmission_mode, command) in cases {
+            assert_eq!(
+                format_set_transmission_mode(&transmission_mode).unwrap(),
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn format_set_channel_pads_to_three_digits() {
+        let cases = [(1, "AT+C001"), (21, "AT+C021"), (127, "AT+C127")];
+
+        for (channel, command) in cases {
+            assert_eq!(
+                format_set_channel(Channel::new_checked(channel)).unwrap(),
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn format_set_transmit_power_covers_all_levels() {
+        let cases = [
+            (1, "AT+P1"),
+            (2, "AT+P2"),
+            (3, "AT+P3"),
+            (4, "AT+P4"),
+            (5, "AT+P5"),
+            (6, "AT+P6"),
+            (7, "AT+P7"),
+            (8, "AT+P8"),
+        ];
+
+        for (level, command) in cases {
+            let transmit_power = TransmitPower::from(PowerLevel::new_checked(level));
+            assert_eq!(format_set_transmit_power(&transmit_power).unwrap(), command);
+        }
+    }
+
+    #[test]
+    fn at_command_overflow_is_config_error() {
+        assert_eq!(
+            AtCommand::new("B")
+                .and_then(|command| command.arg("12345678901"))
+                .err(),
+            Some(Hc12Error::Config)
+        );
+    }
+
+    #[test]
+    fn parse_baud_response_errors() {
+        let cases: [(&[u8], Hc12Error); 5] = [
+            (b"", Hc12Error::BaudRate),
+            (b"OK+", Hc12Error::BaudRate),
+            (b"OK+B\r\n", Hc12Error::BaudRate),
+            (b"OK+B96x0\r\n", Hc12Error::BaudRate),
+            (b"OK+B9601\r\n", Hc12Error::BaudRate),
+        ];
+
+        for (response, error) in cases {
+            assert_eq!(parse_baud_response(response), Err(error), "{:?}", response);
+        }
+        assert_eq!(parse_baud_response(b"OK+B9600\r\n"), Ok(BaudRate::Baud9600));
+    }
+
+    #[test]
+    fn parse_transmission_mode_response_errors() {
+        let cases: [(&[u8], Hc12Error); 4] = [
+            (b"OK+F", Hc12Error::TransmissionMode),
+            (b"OK+FU\r\n", Hc12Error::TransmissionMode),
+            (b"OK+FUx\r\n", Hc12Error::TransmissionMode),
+            (b"OK+FU5\r\n", Hc12Error::TransmissionMode),
+        ];
+
+        for (response, error) in cases {
+            assert_eq!(
+                parse_transmission_mode_response(response),
+                Err(error),
+                "{:?}",
+                response
+            );
+        }
+    }
+
+    #[test]
+    fn parse_transmission_mode_response_keeps_mode_on_bad_baud_rate() {
+        let cases: [&[u8]; 3] = [b"OK+FU4,", b"OK+FU4,Bx\r\n", b"OK+FU4,B1300\r\n"];
+
+        for response in cases {
+            assert_eq!(
+                parse_transmission_mode_response(response),
+                Ok((TransmissionMode::Fu4, Err(Hc12Error::InvalidResponse))),
+                "{:?}",
+                response
+            );
+        }
+    }
+
+    #[test]
+    fn parse_channel_response_errors() {
+        let cases: [(&[u8], Hc12Error); 5] = [
+            (b"OK+", Hc12Error::Channel),
+            (b"OK+C\r\n", Hc12Error::Channel),
+            (b"OK+C0a1\r\n", Hc12Error::Channel),
+            (b"OK+C000\r\n", Hc12Error::Channel),
+            (b"OK+C128\r\n", Hc12Error::Channel),
+        ];
+
+        for (response, error) in cases {
+            assert_eq!(
+                parse_channel_response(response),
+                Err(error),
+                "{:?}",
+                response
+            );
+        }
+        assert_eq!(
+            parse_channel_response(b"OK+C127\r\n"),
+            Ok(Channel::new_checked(127))
+        );
+    }
+
+    #[test]
+    fn parse_transmit_power_response_errors() {
+        let cases: [(&[u8], Hc12Error); 5] = [
+            (b"OK+", Hc12Error::TransmitPower),
+            (b"OK+P\r\n", Hc12Error::TransmitPower),
+            (b"OK+Px\r\n", Hc12Error::TransmitPower),
+            (b"OK+P0\r\n", Hc12Error::TransmitPower),
+            (b"OK+P9\r\n", Hc12Error::TransmitPower),
+        ];
+
+        for (response, error) in cases {
+            assert_eq!(
+                parse_transmit_power_response(response),
+                Err(error),
+                "{:?}",
+                response
+            );
+        }
+        assert_eq!(
+            parse_transmit_power_response(b"OK+P8\r\n"),
+            Ok(TransmitPower::P8)
+        );
+    }
+
+    #[test]
+    fn parse_get_channel_response_errors() {
+        let cases: [&[u8]; 5] = [
+            b"OK+R",
+            b"OK+RC\r\n",
+            b"OK+RCabc\r\n",
+            b"OK+RC000\r\n",
+            b"OK+RC200\r\n",
+        ];
+
+        for response in cases {
+            assert_eq!(
+                parse_get_channel_response(response),
+                Err(Hc12Error::InvalidResponse),
+                "{:?}",
+                response
+            );
+        }
+    }
+
+    #[test]
+    fn parse_params_response_reads_all_settings() {
+        assert_eq!(
+            parse_params_response(b"OK+B9600\r\nOK+RC001\r\nOK+RP:+20dBm\r\nOK+FU3\r\n"),
+            Ok(Hc12Params {
+                baud_rate: BaudRate::Baud9600,
+                channel: Channel::new_checked(1),
+                transmission_mode: TransmissionMode::Fu3,
+                transmit_power: TransmitPower::P8,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_params_response_errors() {
+        let cases: [(&[u8], Hc12Error); 7] = [
+            // Truncated: the mode line never arrived.
+            (
+                b"OK+B9600\r\nOK+RC001\r\nOK+RP:+20dBm\r\n",
+                Hc12Error::InvalidResponse,
+            ),
+            (
+                b"OK+B96x0\r\nOK+RC001\r\nOK+RP:+20dBm\r\nOK+FU3\r\n",
+                Hc12Error::InvalidResponse,
+            ),
+            (
+                b"OK+B9601\r\nOK+RC001\r\nOK+RP:+20dBm\r\nOK+FU3\r\n",
+                Hc12Error::BaudRate,
+            ),
+            (
+                b"OK+B9600\r\nOK+RC128\r\nOK+RP:+20dBm\r\nOK+FU3\r\n",
+                Hc12Error::InvalidResponse,
+            ),
+            (
+                b"OK+B9600\r\nOK+RC001\r\nOK+RP:+xdBm\r\nOK+FU3\r\n",
+                Hc12Error::InvalidResponse,
+            ),
+            (
+                b"OK+B9600\r\nOK+RC001\r\nOK+RP:+21dBm\r\nOK+FU3\r\n",
+                Hc12Error::InvalidResponse,
+            ),
+            (
+                b"OK+B9600\r\nOK+RC001\r\nOK+RP:+20dBm\r\nOK+FU5\r\n",
+                Hc12Error::TransmissionMode,
+            ),
+        ];
+
+        for (response, error) in cases {
+            assert_eq!(
+                parse_params_response(response),
+                Err(error),
+                "{:?}",
+                response
+            );
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use core::convert::Infallible;
+
+use embassy_futures::select::{select, Either};
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::channel::Channel;
+use embassy_sync::pipe::Pipe;
+use embedded_hal::digital::OutputPin;
+use esp_hal::Async;
+use heapless::Vec;
+
+use crate::{FrameCodec, FrameInfo, Hc12, Hc12Error, Hc12Framed};
+
+/// What an RX pump does when its channel is full.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum Backpressure {
+    /// Wait until a consumer makes room. The UART keeps buffering in the meantime and may overrun.
+    #[default]
+    Wait,
+    /// Drop the oldest queued item to make room for the new one.
+    DropOldest,
+    /// Drop the new item.
+    DropNewest,
+}
+
+/// A frame received by [`Hc12Framed::pump_frames`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedFrame<const F: usize> {
+    pub info: FrameInfo,
+    pub payload: Vec<u8, F>,
+}
+
+async fn push<M: RawMutex, T, const Q: usize>(
+    channel: &Channel<M, T, Q>,
+    item: T,
+    backpressure: Backpressure,
+) {
+    match backpressure {
+        Backpressure::Wait => channel.send(item).await,
+        Backpressure::DropOldest => {
+            let mut item = item;
+            while let Err(embassy_sync::channel::TrySendError::Full(rejected)) =
+                channel.try_send(item)
+            {
+                let _ = channel.try_receive();
+                item = rejected;
+            }
+        }
+        Backpressure::DropNewest => {
+            let _ = channel.try_send(item);
+        }
+    }
+}
+
+impl<D, S: OutputPin> Hc12<'_, Async, D, S> {
+    /// Reads continuously and pushes the received bytes into `channel` in chunks of up to `F`
+    /// bytes. Only returns when the UART reports an error. Embassy tasks can't be generic, so
+    /// call this from a task of your own:
+    ///
+    /// ```ignore
+    /// #[embassy_executor::task]
+    /// async fn rx_task(mut hc12: Hc12<'static, Async>, channel: &'static Channel<NoopRawMutex, Vec<u8, 32>, 4>) {
+    ///     let _ = hc12.pump_bytes(channel, Backpressure::Wait).await;
+    /// }
+    /// ```
+    pub async fn pump_bytes<M: RawMutex, const F: usize, const Q: usize>(
+        &mut self,
+        channel: &Channel<M, Vec<u8, F>, Q>,
+        backpressure: Backpressure,
+    ) -> Result<Infallible, Hc12Error> {
+        let mut buffer = [0u8; F];
+
+        loop {
+            let bytes_read = self.read_async(&mut buffer).await?;
+            push(
+                channel,
+                Vec::from_slice(&buffer[..bytes_read]).unwrap(),
+                backpressure,
+            )
+            .await;
+        }
+    }
+
+    /// Moves bytes between the radio and a pair of pipes: received bytes are written into
+    /// `rx_pipe`, bytes written into `tx_pipe` by any number of producers are sent in order with
+    /// [`Hc12::send_chunked`], so its burst limits and pacing apply. While `rx_pipe` is full
+    /// neither direction makes progress and the UART may overrun. Only returns on errors. Like
+    /// [`Hc12::pump_bytes`] this is meant to run inside a task of your own.
+    pub async fn pump_pipes<M: RawMutex, const RX: usize, const TX: usize>(
+        &mut self,
+        rx_pipe: &Pipe<M, RX>,
+        tx_pipe: &Pipe<M, TX>,
+    ) -> Result<Infallible, Hc12Error> {
+        let mut rx_buffer = [0u8; 32];
+        let mut tx_buffer = [0u8; 64];
+
+        loop {
+            match select(
+                self.read_async(&mut rx_buffer),
+                tx_pipe.read(&mut tx_buffer),
+            )
+            .await
+            {
+                Either::First(bytes_read) => rx_pipe.write_all(&rx_buffer[..bytes_read?]).await,
+                Either::Second(bytes_read) => self.send_chunked(&tx_buffer[..bytes_read]).await?,
+            }
+        }
+    }
+}
+
+impl<C: FrameCodec, const N: usize, D, S: OutputPin> Hc12Framed<'_, Async, C, N, D, S> {
+    /// Receives frames continuously and pushes them into `channel`. Frames the codec rejects and
+    /// frames larger than `F` bytes are dropped; only UART errors end the pump. Like
+    /// [`Hc12::pump_bytes`] this is meant to run inside a task of your own.
+    pub async fn pump_frames<M: RawMutex, const F: usize, const Q: usize>(
+        &mut self,
+        channel: &Channel<M, ReceivedFrame<F>, Q>,
+        backpressure: Backpressure,
+    ) -> Result<Infallible, Hc12Error> {
+        let mut buffer = [0u8; F];
+
+        loop {
+            let info = match self.recv_frame(&mut buffer).await {
+                Ok(info) => info,
+                Err(error @ Hc12Error::UartError(_)) => return Err(error),
+                Err(_) => continue,
+            };
+            let frame = ReceivedFrame {
+                info,
+                payload: Vec::from_slice(&buffer[..info.len]).unwrap(),
+            };
+            push(channel, frame, backpressure).await;
+        }
+    }
+}
+
+/// Largest payload yielded by [`Frames`].
+pub const FRAMES_PAYLOAD_LEN: usize = 64;
+
+/// Stream of the frames received by an [`Hc12Framed`], returned by [`Hc12Framed::frames`]. Frames
+/// are only read while [`Frames::next`] is awaited, so the consumer's pace is the backpressure.
+pub struct Frames<'f, 'd, C: FrameCodec, const N: usize, D, S: OutputPin> {
+    framed: &'f mut Hc12Framed<'d, Async, C, N, D, S>,
+}
+
+impl<C: FrameCodec, const N: usize, D, S: OutputPin> Frames<'_, '_, C, N, D, S> {
+    /// Waits for the payload of the next frame. Frames the codec rejects and frames larger than
+    /// [`FRAMES_PAYLOAD_LEN`] bytes are yielded as errors and the stream continues with the
+    /// following frame. The radio never runs out of frames, so this never returns `None`; the
+    /// `Option` only allows `while let Some(frame) = frames.next().await`.
+    pub async fn next(&mut self) -> Option<Result<Vec<u8, FRAMES_PAYLOAD_LEN>, Hc12Error>> {
+        let mut buffer = [0u8; FRAMES_PAYLOAD_LEN];
+
+        let result = self
+            .framed
+            .recv_frame(&mut buffer)
+            .await
+            .map(|info| Vec::from_slice(&buffer[..info.len]).unwrap());
+
+        Some(result)
+    }
+}
+
+impl<'d, C: FrameCodec, const N: usize, D, S: OutputPin> Hc12Framed<'d, Async, C, N, D, S> {
+    /// Received frames as a stream, for consumers that would rather not manage buffers:
+    ///
+    /// ```ignore
+    /// let mut frames = framed.frames();
+    /// while let Some(frame) = frames.next().await {
+    ///     match frame {
+    ///         Ok(payload) => handle(&payload),
+    ///         Err(error) => warn!("dropped frame: {:?}", error),
+    ///     }
+    /// }
+    /// ```
+    pub fn frames(&mut self) -> Frames<'_, 'd, C, N, D, S> {
+        Frames { framed: self }
+    }
+}
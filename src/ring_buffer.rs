@@ -0,0 +1,189 @@
+// Only `Hc12` buffers its RX with it; without esp32c3 just the tests use it.
+#![cfg_attr(not(feature = "esp32c3"), allow(dead_code))]
+
+/// Fixed-size FIFO of bytes that wraps around instead of moving its contents.
+pub(crate) struct RingBuffer<const N: usize> {
+    buffer: [u8; N],
+    start: usize,
+    len: usize,
+}
+
+impl<const N: usize> RingBuffer<N> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            buffer: [0; N],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Number of bytes that can still be pushed.
+    pub(crate) fn free(&self) -> usize {
+        N - self.len
+    }
+
+    /// The byte `index` positions after the oldest one.
+    pub(crate) fn get(&self, index: usize) -> Option<u8> {
+        (index < self.len).then(|| self.buffer[(self.start + index) % N])
+    }
+
+    /// The oldest bytes up to the end of the backing array, i.e. all bytes unless they wrap
+    /// around.
+    pub(crate) fn contiguous(&self) -> &[u8] {
+        &self.buffer[self.start..N.min(self.start + self.len)]
+    }
+
+    /// Position of the first occurrence of `pattern`.
+    #[cfg(feature = "async")]
+    pub(crate) fn position(&self, pattern: &[u8]) -> Option<usize> {
+        (0..(self.len + 1).saturating_sub(pattern.len())).find(|&index| {
+            pattern
+                .iter()
+                .enumerate()
+                .all(|(offset, &byte)| self.get(index + offset) == Some(byte))
+        })
+    }
+
+    /// Moves the bytes before the first `terminator` into `buffer` and drops the terminator.
+    /// Returns `None` while no complete line is buffered, and `Some(Err(len))` if the line of `len`
+    /// bytes doesn't fit into `buffer`, in which case it is dropped as well.
+    #[cfg(feature = "async")]
+    pub(crate) fn pop_line(
+        &mut self,
+        terminator: &[u8],
+        buffer: &mut [u8],
+    ) -> Option<Result<usize, usize>> {
+        let len = self.position(terminator)?;
+        if len > buffer.len() {
+            self.discard(len + terminator.len());
+            return Some(Err(len));
+        }
+
+        self.pop_into(&mut buffer[..len]);
+        self.discard(terminator.len());
+        Some(Ok(len))
+    }
+
+    /// Appends as much of `data` as fits and returns how many bytes were taken.
+    pub(crate) fn push_slice(&mut self, data: &[u8]) -> usize {
+        let len = data.len().min(self.free());
+        for (offset, &byte) in data[..len].iter().enumerate() {
+            self.buffer[(self.start + self.len + offset) % N] = byte;
+        }
+        self.len += len;
+
+        len
+    }
+
+    /// Moves the oldest bytes into `buffer` and returns how many were moved.
+    pub(crate) fn pop_into(&mut self, buffer: &mut [u8]) -> usize {
+        let len = self.len.min(buffer.len());
+        for (index, byte) in buffer[..len].iter_mut().enumerate() {
+            *byte = self.buffer[(self.start + index) % N];
+        }
+
+        self.discard(len)
+    }
+
+    /// Drops up to `len` of the oldest bytes and returns how many were dropped.
+    pub(crate) fn discard(&mut self, len: usize) -> usize {
+        let len = len.min(self.len);
+        self.start = (self.start + len) % N;
+        self.len -= len;
+
+        len
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.start = 0;
+        self.len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `Hc12::fill_rx_buffer` pushes whatever one UART read returned, and a read or line future
+    // that is dropped between two fills leaves the buffer as it is. These tests replay such
+    // sequences on the buffer the driver reads through.
+
+    #[test]
+    fn partial_fill_survives_for_next_read() {
+        let mut rx_buffer = RingBuffer::<8>::new();
+        rx_buffer.push_slice(b"abc");
+
+        // The read or wait that filled the buffer was cancelled; the next read starts with them.
+        let mut buffer = [0u8; 2];
+        assert_eq!(rx_buffer.pop_into(&mut buffer), 2);
+        assert_eq!(&buffer, b"ab");
+
+        rx_buffer.push_slice(b"de");
+        let mut buffer = [0u8; 8];
+        assert_eq!(rx_buffer.pop_into(&mut buffer), 3);
+        assert_eq!(&buffer[..3], b"cde");
+        assert_eq!(rx_buffer.len(), 0);
+    }
+
+    #[test]
+    fn fill_beyond_capacity_keeps_oldest_bytes() {
+        let mut rx_buffer = RingBuffer::<4>::new();
+
+        assert_eq!(rx_buffer.push_slice(b"abcdef"), 4);
+        assert_eq!(rx_buffer.free(), 0);
+
+        let mut buffer = [0u8; 4];
+        assert_eq!(rx_buffer.pop_into(&mut buffer), 4);
+        assert_eq!(&buffer, b"abcd");
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn partial_line_survives_cancelled_receive_line() {
+        let mut rx_buffer = RingBuffer::<8>::new();
+        let mut buffer = [0u8; 8];
+
+        // Two fills that wrap around the end of the backing array, the first of them cut short
+        // inside the terminator.
+        rx_buffer.push_slice(b"xxxxx");
+        rx_buffer.discard(5);
+        rx_buffer.push_slice(b"line\r");
+        assert_eq!(rx_buffer.pop_line(b"\r\n", &mut buffer), None);
+
+        rx_buffer.push_slice(b"\nnx");
+        assert_eq!(rx_buffer.pop_line(b"\r\n", &mut buffer), Some(Ok(4)));
+        assert_eq!(&buffer[..4], b"line");
+
+        // The start of the next line stays for the next read.
+        let mut rest = [0u8; 8];
+        assert_eq!(rx_buffer.pop_into(&mut rest), 2);
+        assert_eq!(&rest[..2], b"nx");
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn line_too_long_is_dropped_with_terminator() {
+        let mut rx_buffer = RingBuffer::<16>::new();
+        let mut buffer = [0u8; 3];
+        rx_buffer.push_slice(b"toolong\nok\n");
+
+        assert_eq!(rx_buffer.pop_line(b"\n", &mut buffer), Some(Err(7)));
+        assert_eq!(rx_buffer.pop_line(b"\n", &mut buffer), Some(Ok(2)));
+        assert_eq!(&buffer[..2], b"ok");
+        assert!(rx_buffer.is_empty());
+    }
+}
@@ -0,0 +1,575 @@
+use core::ops::RangeInclusive;
+use embedded_hal::digital::{OutputPin, PinState};
+
+use crate::Hc12Error;
+
+/// A radio channel in `1..=127`, the range the module accepts in FU1 and FU3. Channel `n` sits at
+/// 433.0 MHz + `n` × 400 kHz.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Channel(u8);
+
+impl Channel {
+    pub const MIN: Channel = Channel(1);
+    pub const MAX: Channel = Channel(127);
+
+    /// Returns `None` if `channel` is outside `1..=127`.
+    pub const fn new(channel: u8) -> Option<Self> {
+        if channel >= Self::MIN.0 && channel <= Self::MAX.0 {
+            Some(Self(channel))
+        } else {
+            None
+        }
+    }
+
+    /// Like [`Channel::new`], but panics on invalid channels. In a `const` context this turns an
+    /// invalid literal into a compile error:
+    ///
+    /// ```no_run
+    /// # use hc_12::Channel;
+    /// const CHANNEL: Channel = Channel::new_checked(42);
+    /// # assert_eq!(CHANNEL.get(), 42);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// # use hc_12::Channel;
+    /// const CHANNEL: Channel = Channel::new_checked(128);
+    /// # let _ = CHANNEL;
+    /// ```
+    pub const fn new_checked(channel: u8) -> Self {
+        match Self::new(channel) {
+            Some(channel) => channel,
+            None => panic!("HC-12 channels are 1..=127"),
+        }
+    }
+
+    pub const fn get(&self) -> u8 {
+        self.0
+    }
+}
+
+impl Default for Channel {
+    fn default() -> Self {
+        Self::MIN
+    }
+}
+
+impl From<Channel> for u8 {
+    fn from(channel: Channel) -> Self {
+        channel.0
+    }
+}
+
+impl TryFrom<u8> for Channel {
+    type Error = Hc12Error;
+
+    fn try_from(channel: u8) -> Result<Self, Self::Error> {
+        Self::new(channel).ok_or(Hc12Error::Channel)
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum TransmissionMode {
+    Fu1,
+    Fu2,
+    #[default]
+    Fu3,
+    Fu4,
+}
+
+impl TransmissionMode {
+    /// Channels the module honors in this mode. The low air rates of FU2 and FU4 are only
+    /// supported up to channel 100; FU1 and FU3 accept the full `1..=127` range.
+    pub const fn channels(&self) -> RangeInclusive<u8> {
+        match self {
+            TransmissionMode::Fu1 | TransmissionMode::Fu3 => 1..=127,
+            TransmissionMode::Fu2 | TransmissionMode::Fu4 => 1..=100,
+        }
+    }
+
+    /// Over-the-air data rate in bits per second when the serial port runs at `baud_rate`.
+    pub const fn air_rate(&self, baud_rate: &BaudRate) -> u32 {
+        match self {
+            TransmissionMode::Fu1 | TransmissionMode::Fu2 => 250_000,
+            TransmissionMode::Fu3 => match baud_rate {
+                BaudRate::Baud1200 | BaudRate::Baud2400 => 5_000,
+                BaudRate::Baud4800 => 15_000,
+                BaudRate::Baud9600 | BaudRate::Baud19200 => 58_000,
+                BaudRate::Baud38400 | BaudRate::Baud57600 | BaudRate::Baud115200 => 236_000,
+            },
+            TransmissionMode::Fu4 => 500,
+        }
+    }
+
+    /// Largest burst in bytes the module reliably packetizes in this mode. FU2 and FU4 send at
+    /// most 60 bytes per packet; FU1 and FU3 are bounded by the module's 64 byte buffer.
+    pub const fn max_burst(&self) -> usize {
+        match self {
+            TransmissionMode::Fu1 | TransmissionMode::Fu3 => 64,
+            TransmissionMode::Fu2 | TransmissionMode::Fu4 => 60,
+        }
+    }
+
+    /// Bytes that can be written in one burst at `baud_rate` without overrunning the module. FU3
+    /// and FU4 collect a burst into one radio packet. FU1 and FU2 stream, sending while bytes
+    /// still arrive, so the buffer only has to hold what the radio falls behind by; the module
+    /// still sends at most [`TransmissionMode::max_burst`] bytes per packet, which caps the burst.
+    pub fn max_chunk(&self, baud_rate: &BaudRate) -> usize {
+        let burst = self.max_burst();
+
+        match self {
+            TransmissionMode::Fu3 | TransmissionMode::Fu4 => burst,
+            TransmissionMode::Fu1 | TransmissionMode::Fu2 => {
+                let baud_rate_bps = u32::from(baud_rate);
+                let air_rate = self.air_rate(baud_rate);
+                if air_rate >= baud_rate_bps {
+                    return burst;
+                }
+
+                // A burst of n bytes leaves n * (baud - air) / baud of them in the buffer.
+                (burst * baud_rate_bps as usize / (baud_rate_bps - air_rate) as usize).min(burst)
+            }
+        }
+    }
+
+    /// Whether the module accepts `baud_rate` as its serial rate in this mode. FU2 is limited to
+    /// 1200, 2400 and 4800 baud, FU4 to 1200 baud.
+    pub const fn supports_baud_rate(&self, baud_rate: &BaudRate) -> bool {
+        match self {
+            TransmissionMode::Fu1 | TransmissionMode::Fu3 => true,
+            TransmissionMode::Fu2 => matches!(
+                baud_rate,
+                BaudRate::Baud1200 | BaudRate::Baud2400 | BaudRate::Baud4800
+            ),
+            TransmissionMode::Fu4 => matches!(baud_rate, BaudRate::Baud1200),
+        }
+    }
+
+    /// Minimum pause in milliseconds the datasheet requires between bursts in this mode, on top of
+    /// their air time. FU2 loses data if packets follow each other within two seconds.
+    pub const fn min_burst_gap_ms(&self) -> u32 {
+        match self {
+            TransmissionMode::Fu2 => 2_000,
+            TransmissionMode::Fu1 | TransmissionMode::Fu3 | TransmissionMode::Fu4 => 0,
+        }
+    }
+
+    /// Time in milliseconds the module needs to send `bytes` over the air in this mode at
+    /// `baud_rate`, rounded up.
+    pub fn air_time_ms(&self, baud_rate: &BaudRate, bytes: usize) -> u32 {
+        let bits = bytes as u32 * 10;
+        (bits * 1000).div_ceil(self.air_rate(baud_rate))
+    }
+
+    /// Pause in milliseconds after a burst of `bytes` before the next one may follow in this mode
+    /// at `baud_rate`.
+    pub fn burst_gap_ms(&self, baud_rate: &BaudRate, bytes: usize) -> u32 {
+        self.air_time_ms(baud_rate, bytes)
+            .max(self.min_burst_gap_ms())
+    }
+}
+
+impl From<&TransmissionMode> for u32 {
+    fn from(transmission_mode: &TransmissionMode) -> Self {
+        match transmission_mode {
+            TransmissionMode::Fu1 => 1,
+            TransmissionMode::Fu2 => 2,
+            TransmissionMode::Fu3 => 3,
+            TransmissionMode::Fu4 => 4,
+        }
+    }
+}
+
+impl TryFrom<u32> for TransmissionMode {
+    type Error = Hc12Error;
+
+    fn try_from(transmission_mode: u32) -> Result<Self, Self::Error> {
+        match transmission_mode {
+            1 => Ok(TransmissionMode::Fu1),
+            2 => Ok(TransmissionMode::Fu2),
+            3 => Ok(TransmissionMode::Fu3),
+            4 => Ok(TransmissionMode::Fu4),
+            _ => Err(Hc12Error::TransmissionMode),
+        }
+    }
+}
+
+impl From<TransmissionMode> for u32 {
+    fn from(transmission_mode: TransmissionMode) -> Self {
+        match transmission_mode {
+            TransmissionMode::Fu1 => 1,
+            TransmissionMode::Fu2 => 2,
+            TransmissionMode::Fu3 => 3,
+            TransmissionMode::Fu4 => 4,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum BaudRate {
+    Baud1200,
+    Baud2400,
+    Baud4800,
+    #[default]
+    Baud9600,
+    Baud19200,
+    Baud38400,
+    Baud57600,
+    Baud115200,
+}
+
+impl From<&BaudRate> for &str {
+    fn from(value: &BaudRate) -> Self {
+        match value {
+            BaudRate::Baud1200 => "1200",
+            BaudRate::Baud2400 => "2400",
+            BaudRate::Baud4800 => "4800",
+            BaudRate::Baud9600 => "9600",
+            BaudRate::Baud19200 => "19200",
+            BaudRate::Baud38400 => "38400",
+            BaudRate::Baud57600 => "57600",
+            BaudRate::Baud115200 => "115200",
+        }
+    }
+}
+
+impl From<BaudRate> for u32 {
+    fn from(baud_rate: BaudRate) -> Self {
+        match baud_rate {
+            BaudRate::Baud1200 => 1200,
+            BaudRate::Baud2400 => 2400,
+            BaudRate::Baud4800 => 4800,
+            BaudRate::Baud9600 => 9600,
+            BaudRate::Baud19200 => 19200,
+            BaudRate::Baud38400 => 38400,
+            BaudRate::Baud57600 => 57600,
+            BaudRate::Baud115200 => 115200,
+        }
+    }
+}
+
+impl From<&BaudRate> for u32 {
+    fn from(baud_rate: &BaudRate) -> Self {
+        match baud_rate {
+            BaudRate::Baud1200 => 1200,
+            BaudRate::Baud2400 => 2400,
+            BaudRate::Baud4800 => 4800,
+            BaudRate::Baud9600 => 9600,
+            BaudRate::Baud19200 => 19200,
+            BaudRate::Baud38400 => 38400,
+            BaudRate::Baud57600 => 57600,
+            BaudRate::Baud115200 => 115200,
+        }
+    }
+}
+
+impl BaudRate {
+    /// Time `auto_baud` lets the UART settle at this rate before probing with `AT`: a fixed 5 ms
+    /// plus the time `AT\r\n` and `OK\r\n` take on the wire, so slow rates get more time and fast
+    /// ones less.
+    pub(crate) fn probe_settle_ms(&self) -> u32 {
+        const PROBE_BITS: u32 = 8 * 10;
+
+        5 + (PROBE_BITS * 1000).div_ceil(u32::from(self))
+    }
+}
+
+impl TryFrom<u32> for BaudRate {
+    type Error = Hc12Error;
+
+    fn try_from(baud_rate: u32) -> Result<Self, Self::Error> {
+        match baud_rate {
+            1200 => Ok(BaudRate::Baud1200),
+            2400 => Ok(BaudRate::Baud2400),
+            4800 => Ok(BaudRate::Baud4800),
+            9600 => Ok(BaudRate::Baud9600),
+            19200 => Ok(BaudRate::Baud19200),
+            38400 => Ok(BaudRate::Baud38400),
+            57600 => Ok(BaudRate::Baud57600),
+            115200 => Ok(BaudRate::Baud115200),
+            _ => Err(Hc12Error::BaudRate),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum TransmitPower {
+    P1,
+    P2,
+    P3,
+    P4,
+    P5,
+    P6,
+    P7,
+    #[default]
+    P8,
+}
+
+impl TransmitPower {
+    /// Output power in dBm as documented in the datasheet.
+    pub const fn dbm(&self) -> i8 {
+        match self {
+            TransmitPower::P1 => -1,
+            TransmitPower::P2 => 2,
+            TransmitPower::P3 => 5,
+            TransmitPower::P4 => 8,
+            TransmitPower::P5 => 11,
+            TransmitPower::P6 => 14,
+            TransmitPower::P7 => 17,
+            TransmitPower::P8 => 20,
+        }
+    }
+
+    /// Rough open-field range in meters in `mode`. Starts from the datasheet's typical range at
+    /// 20 dBm (100 m in FU1 and FU2, 1000 m in FU3, 1800 m in FU4) and scales it down with the
+    /// free-space path loss of the lower output power. Real-world range depends heavily on the
+    /// antennas and surroundings.
+    pub const fn estimate_range_m(&self, mode: &TransmissionMode) -> u32 {
+        let range_at_max_power = match mode {
+            TransmissionMode::Fu1 | TransmissionMode::Fu2 => 100,
+            TransmissionMode::Fu3 => 1_000,
+            TransmissionMode::Fu4 => 1_800,
+        };
+        // 10^(-dB / 20) in thousandths for every 3 dB below the maximum power.
+        let factor = match self {
+            TransmitPower::P1 => 89,
+            TransmitPower::P2 => 126,
+            TransmitPower::P3 => 178,
+            TransmitPower::P4 => 251,
+            TransmitPower::P5 => 355,
+            TransmitPower::P6 => 501,
+            TransmitPower::P7 => 708,
+            TransmitPower::P8 => 1_000,
+        };
+
+        range_at_max_power * factor / 1_000
+    }
+}
+
+/// A power level in `1..=8`, the argument of `AT+P`, for tables of settings built from numbers.
+/// Converts into the matching [`TransmitPower`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PowerLevel(u8);
+
+impl PowerLevel {
+    pub const MIN: PowerLevel = PowerLevel(1);
+    pub const MAX: PowerLevel = PowerLevel(8);
+
+    /// Returns `None` if `level` is outside `1..=8`.
+    pub const fn new(level: u8) -> Option<Self> {
+        if level >= Self::MIN.0 && level <= Self::MAX.0 {
+            Some(Self(level))
+        } else {
+            None
+        }
+    }
+
+    /// Like [`PowerLevel::new`], but panics on invalid levels. In a `const` context this turns an
+    /// invalid literal into a compile error:
+    ///
+    /// ```no_run
+    /// # use hc_12::PowerLevel;
+    /// const POWER_LEVEL: PowerLevel = PowerLevel::new_checked(4);
+    /// # assert_eq!(POWER_LEVEL.get(), 4);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// # use hc_12::PowerLevel;
+    /// const POWER_LEVEL: PowerLevel = PowerLevel::new_checked(9);
+    /// # let _ = POWER_LEVEL;
+    /// ```
+    pub const fn new_checked(level: u8) -> Self {
+        match Self::new(level) {
+            Some(power_level) => power_level,
+            None => panic!("HC-12 power levels are 1..=8"),
+        }
+    }
+
+    pub const fn get(&self) -> u8 {
+        self.0
+    }
+}
+
+impl TryFrom<u8> for PowerLevel {
+    type Error = Hc12Error;
+
+    fn try_from(level: u8) -> Result<Self, Self::Error> {
+        Self::new(level).ok_or(Hc12Error::TransmitPower)
+    }
+}
+
+impl From<PowerLevel> for TransmitPower {
+    fn from(power_level: PowerLevel) -> Self {
+        match power_level.0 {
+            1 => TransmitPower::P1,
+            2 => TransmitPower::P2,
+            3 => TransmitPower::P3,
+            4 => TransmitPower::P4,
+            5 => TransmitPower::P5,
+            6 => TransmitPower::P6,
+            7 => TransmitPower::P7,
+            _ => TransmitPower::P8,
+        }
+    }
+}
+
+impl From<TransmitPower> for PowerLevel {
+    fn from(transmit_power: TransmitPower) -> Self {
+        Self(u32::from(&transmit_power) as u8)
+    }
+}
+
+impl From<&TransmitPower> for u32 {
+    fn from(transmit_power: &TransmitPower) -> Self {
+        match transmit_power {
+            TransmitPower::P1 => 1,
+            TransmitPower::P2 => 2,
+            TransmitPower::P3 => 3,
+            TransmitPower::P4 => 4,
+            TransmitPower::P5 => 5,
+            TransmitPower::P6 => 6,
+            TransmitPower::P7 => 7,
+            TransmitPower::P8 => 8,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum LineTerminator {
+    #[default]
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl From<&LineTerminator> for &[u8] {
+    fn from(value: &LineTerminator) -> Self {
+        match value {
+            LineTerminator::Lf => b"\n",
+            LineTerminator::CrLf => b"\r\n",
+            LineTerminator::Cr => b"\r",
+        }
+    }
+}
+
+/// Level of the SET line that selects command mode. The bare module enters command mode with SET
+/// pulled low, some carrier boards invert the line with a transistor and need it driven high.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum SetPolarity {
+    #[default]
+    ActiveLow,
+    ActiveHigh,
+}
+
+impl SetPolarity {
+    /// SET level that puts the module into command mode.
+    pub fn command_level(&self) -> PinState {
+        match self {
+            SetPolarity::ActiveLow => PinState::Low,
+            SetPolarity::ActiveHigh => PinState::High,
+        }
+    }
+
+    /// SET level that puts the module into transparent mode.
+    pub fn transparent_level(&self) -> PinState {
+        match self {
+            SetPolarity::ActiveLow => PinState::High,
+            SetPolarity::ActiveHigh => PinState::Low,
+        }
+    }
+}
+
+/// Drives a SET pin to `level`, reporting pin errors, e.g. of an I/O expander, as
+/// [`Hc12Error::Io`].
+pub(crate) fn drive_pin<S: OutputPin>(pin: &mut S, level: PinState) -> Result<(), Hc12Error> {
+    pin.set_state(level).map_err(|_| Hc12Error::Io)
+}
+
+/// Configuration reported by the module itself, as read by
+/// [`Hc12::get_params`](crate::Hc12::get_params).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hc12Params {
+    pub baud_rate: BaudRate,
+    pub channel: Channel,
+    pub transmission_mode: TransmissionMode,
+    pub transmit_power: TransmitPower,
+}
+
+impl Hc12Params {
+    /// Center frequency of the channel in kHz. Channel 1 sits at 433.4 MHz, each further channel
+    /// adds 400 kHz.
+    pub const fn frequency_khz(&self) -> u32 {
+        433_000 + self.channel.get() as u32 * 400
+    }
+
+    /// Logs the parameters as a block of lines, e.g. for bug reports.
+    #[cfg(feature = "esp32c3")]
+    pub(crate) fn log(&self) {
+        let frequency_khz = self.frequency_khz();
+
+        info!("HC-12 configuration:");
+        info!("  baud rate: {}", u32::from(self.baud_rate));
+        info!("  channel:   {}", self.channel.get());
+        info!(
+            "  frequency: {}.{} MHz",
+            frequency_khz / 1000,
+            frequency_khz % 1000 / 100
+        );
+        info!("  mode:      FU{}", u32::from(self.transmission_mode));
+        info!("  power:     {} dBm", self.transmit_power.dbm());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command;
+
+    const TRANSMISSION_MODES: [TransmissionMode; 4] = [
+        TransmissionMode::Fu1,
+        TransmissionMode::Fu2,
+        TransmissionMode::Fu3,
+        TransmissionMode::Fu4,
+    ];
+
+    #[test]
+    fn max_chunk_fits_into_a_packet() {
+        for transmission_mode in TRANSMISSION_MODES {
+            for baud_rate in command::PROBE_BAUD_RATES {
+                let chunk = transmission_mode.max_chunk(&baud_rate);
+                assert!(chunk > 0);
+                assert!(chunk <= transmission_mode.max_burst());
+            }
+        }
+    }
+
+    #[test]
+    fn max_chunk_of_fu2_is_one_packet() {
+        assert_eq!(TransmissionMode::Fu2.max_chunk(&BaudRate::Baud4800), 60);
+    }
+
+    #[test]
+    fn burst_gap_of_fu2_keeps_packet_spacing() {
+        let chunk = TransmissionMode::Fu2.max_chunk(&BaudRate::Baud4800);
+
+        assert_eq!(
+            TransmissionMode::Fu2.air_time_ms(&BaudRate::Baud4800, chunk),
+            3
+        );
+        assert_eq!(
+            TransmissionMode::Fu2.burst_gap_ms(&BaudRate::Baud4800, chunk),
+            2_000
+        );
+    }
+
+    #[test]
+    fn burst_gap_outside_fu2_is_air_time() {
+        assert_eq!(
+            TransmissionMode::Fu4.burst_gap_ms(&BaudRate::Baud1200, 60),
+            TransmissionMode::Fu4.air_time_ms(&BaudRate::Baud1200, 60)
+        );
+        assert_eq!(
+            TransmissionMode::Fu3.burst_gap_ms(&BaudRate::Baud9600, 64),
+            TransmissionMode::Fu3.air_time_ms(&BaudRate::Baud9600, 64)
+        );
+    }
+}
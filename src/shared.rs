@@ -0,0 +1,65 @@
+use embassy_sync::blocking_mutex::raw::{CriticalSectionRawMutex, RawMutex};
+use embassy_sync::mutex::{Mutex, MutexGuard};
+use embassy_time::Timer;
+use embedded_hal::digital::OutputPin;
+use esp_hal::{delay::Delay, gpio::OutputOpenDrain, Async};
+
+use crate::{Hc12, Hc12Error};
+
+/// Interval in which [`SharedHc12::recv`] checks for received bytes while not holding the lock.
+pub const SHARED_RECV_POLL_MS: u64 = 5;
+
+/// An async [`Hc12`] behind an `embassy_sync` mutex, so that several tasks, typically one sending
+/// and one receiving, can share the radio through `&SharedHc12`.
+///
+/// `send` and `recv` only hold the lock while they access the driver. Don't keep the guard
+/// returned by [`SharedHc12::lock`] across long awaits, e.g. waiting for incoming data, as every
+/// other task using the radio is blocked until it is dropped.
+pub struct SharedHc12<
+    'd,
+    M: RawMutex = CriticalSectionRawMutex,
+    D = Delay,
+    S: OutputPin = OutputOpenDrain<'d>,
+> {
+    hc12: Mutex<M, Hc12<'d, Async, D, S>>,
+}
+
+impl<'d, M: RawMutex, D, S: OutputPin> SharedHc12<'d, M, D, S> {
+    pub const fn new(hc12: Hc12<'d, Async, D, S>) -> Self {
+        Self {
+            hc12: Mutex::new(hc12),
+        }
+    }
+
+    /// Exclusive access to the driver, e.g. for configuration commands.
+    pub async fn lock(&self) -> MutexGuard<'_, M, Hc12<'d, Async, D, S>> {
+        self.hc12.lock().await
+    }
+
+    pub fn into_inner(self) -> Hc12<'d, Async, D, S> {
+        self.hc12.into_inner()
+    }
+
+    /// Sends `data` with [`Hc12::send_chunked`] while holding the lock.
+    pub async fn send(&self, data: &[u8]) -> Result<(), Hc12Error> {
+        self.hc12.lock().await.send_chunked(data).await
+    }
+
+    /// Waits until bytes have been received and reads as many as fit into `buffer`. The lock is
+    /// released between checks every [`SHARED_RECV_POLL_MS`], so waiting for data doesn't block
+    /// senders.
+    pub async fn recv(&self, buffer: &mut [u8]) -> Result<usize, Hc12Error> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let bytes_read = self.hc12.lock().await.try_read(buffer)?;
+            if bytes_read != 0 {
+                return Ok(bytes_read);
+            }
+
+            Timer::after_millis(SHARED_RECV_POLL_MS).await;
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+#[cfg(feature = "async")]
+use esp_hal::Async;
+use esp_hal::{
+    delay::Delay, gpio::OutputOpenDrain, uart::Error as UartError, Blocking, DriverMode,
+};
+
+use crate::{Hc12, Hc12Config, Hc12Error, Hc12Params};
+
+/// When [`SupervisedHc12`] suspects a reboot of the module.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SupervisionPolicy {
+    /// Time without received data after which the module is checked, `None` to never check for
+    /// silence alone. The silence restarts with every check.
+    pub silence_ms: Option<u32>,
+    /// Number of framing, parity or glitch errors in a row that trigger a check, as seen while
+    /// the module restarts at a baud rate other than the host's. `0` disables the trigger.
+    pub error_burst: u8,
+}
+
+impl Default for SupervisionPolicy {
+    fn default() -> Self {
+        Self {
+            silence_ms: Some(10_000),
+            error_burst: 3,
+        }
+    }
+}
+
+/// Outcome of the last check of [`SupervisedHc12`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SupervisionStatus {
+    /// Not checked yet.
+    Unchecked,
+    /// The module answered with the expected configuration.
+    Healthy,
+    /// The module came back with another baud rate or other settings, most likely after a reset,
+    /// and the expected configuration was applied again.
+    Restored,
+    /// The module didn't answer at any baud rate, or the configuration couldn't be applied.
+    Unreachable,
+}
+
+/// An [`Hc12`] watched for brown-outs and other resets, after which the module runs with its
+/// stored settings instead of those applied at runtime, e.g. a temporary channel change.
+///
+/// Reads through [`SupervisedHc12::try_read`] or [`SupervisedHc12::read_async`] count UART
+/// errors, and [`SupervisedHc12::supervise`] checks the module once the [`SupervisionPolicy`]
+/// calls for it: `AT` at the current baud rate, then `AT+RX` compared against the expected
+/// [`Hc12Config`]. A module that doesn't answer or runs with other settings gets the
+/// configuration applied again with `bring_up`. The result is kept in
+/// [`SupervisedHc12::status`] for the application to poll.
+///
+/// A check holds the module in command mode for about a second, during which nothing is
+/// received over the air, so the silence timeout should be well above the expected gaps in
+/// traffic.
+pub struct SupervisedHc12<'d, Dm: DriverMode, D = Delay, S: OutputPin = OutputOpenDrain<'d>> {
+    hc12: Hc12<'d, Dm, D, S>,
+    config: Hc12Config,
+    policy: SupervisionPolicy,
+    errors: u8,
+    last_check_ms: u64,
+    status: SupervisionStatus,
+}
+
+fn now_ms() -> u64 {
+    esp_hal::time::now().duration_since_epoch().to_millis()
+}
+
+impl<'d, Dm: DriverMode, D, S: OutputPin> SupervisedHc12<'d, Dm, D, S> {
+    /// Watches a driver whose module is expected to run `config`, e.g. after `bring_up`. Change
+    /// the expectation with [`SupervisedHc12::set_config`] when changing settings at runtime.
+    pub fn new(hc12: Hc12<'d, Dm, D, S>, config: Hc12Config, policy: SupervisionPolicy) -> Self {
+        Self {
+            hc12,
+            config,
+            policy,
+            errors: 0,
+            last_check_ms: now_ms(),
+            status: SupervisionStatus::Unchecked,
+        }
+    }
+
+    pub fn config(&self) -> &Hc12Config {
+        &self.config
+    }
+
+    /// Configuration applied again when the module comes back with other settings.
+    pub fn set_config(&mut self, config: Hc12Config) {
+        self.config = config;
+    }
+
+    pub fn status(&self) -> SupervisionStatus {
+        self.status
+    }
+
+    pub fn inner(&mut self) -> &mut Hc12<'d, Dm, D, S> {
+        &mut self.hc12
+    }
+
+    pub fn into_inner(self) -> Hc12<'d, Dm, D, S> {
+        self.hc12
+    }
+
+    /// [`Hc12::try_read`], counting UART errors towards [`SupervisionPolicy::error_burst`].
+    pub fn try_read(&mut self, buffer: &mut [u8]) -> Result<usize, UartError> {
+        let result = self.hc12.try_read(buffer);
+        self.note_read(&result);
+
+        result
+    }
+
+    /// Whether the policy calls for a check now.
+    pub fn check_due(&self) -> bool {
+        if self.policy.error_burst != 0 && self.errors >= self.policy.error_burst {
+            return true;
+        }
+
+        let last_activity_ms = self
+            .hc12
+            .last_rx_ms
+            .map_or(self.last_check_ms, |last_rx_ms| {
+                last_rx_ms.max(self.last_check_ms)
+            });
+        self.policy.silence_ms.is_some_and(|silence_ms| {
+            now_ms().saturating_sub(last_activity_ms) >= u64::from(silence_ms)
+        })
+    }
+
+    fn note_read(&mut self, result: &Result<usize, UartError>) {
+        match result {
+            Err(
+                UartError::RxFrameError | UartError::RxParityError | UartError::RxGlitchDetected,
+            ) => {
+                self.errors = self.errors.saturating_add(1);
+            }
+            Ok(bytes_read) if *bytes_read != 0 => self.errors = 0,
+            _ => {}
+        }
+    }
+
+    fn matches_config(&self, params: &Hc12Params) -> bool {
+        params.baud_rate == self.config.baud_rate
+            && params.channel == self.config.channel
+            && params.transmission_mode == self.config.transmission_mode
+            && params.transmit_power == self.config.transmit_power
+    }
+
+    /// Records the outcome of a check and restarts the triggers.
+    fn finish_check(
+        &mut self,
+        result: Result<SupervisionStatus, Hc12Error>,
+    ) -> Result<SupervisionStatus, Hc12Error> {
+        self.errors = 0;
+        self.last_check_ms = now_ms();
+        self.status = match result {
+            Ok(status) => status,
+            Err(_) => SupervisionStatus::Unreachable,
+        };
+        if self.status == SupervisionStatus::Restored {
+            info!("HC-12 came back with other settings, configuration restored");
+        }
+
+        result
+    }
+}
+
+impl<D: DelayNs, S: OutputPin> SupervisedHc12<'_, Blocking, D, S> {
+    /// Checks the module if [`SupervisedHc12::check_due`], otherwise returns the last status.
+    pub fn supervise(&mut self) -> Result<SupervisionStatus, Hc12Error> {
+        if !self.check_due() {
+            return Ok(self.status);
+        }
+
+        self.check()
+    }
+
+    /// Checks the module right away and applies the configuration again if it doesn't answer or
+    /// runs with other settings. Fails with the error of `bring_up` if that doesn't help, leaving
+    /// [`SupervisionStatus::Unreachable`].
+    pub fn check(&mut self) -> Result<SupervisionStatus, Hc12Error> {
+        let result = self.probe();
+        self.finish_check(result)
+    }
+
+    fn probe(&mut self) -> Result<SupervisionStatus, Hc12Error> {
+        if self.hc12.test().is_ok() {
+            if let Ok(params) = self.hc12.get_params() {
+                if self.matches_config(&params) {
+                    return Ok(SupervisionStatus::Healthy);
+                }
+            }
+        }
+
+        self.hc12.bring_up(&self.config)?;
+        Ok(SupervisionStatus::Restored)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<D, S: OutputPin> SupervisedHc12<'_, Async, D, S> {
+    /// [`Hc12::read_async`], counting UART errors towards [`SupervisionPolicy::error_burst`].
+    pub async fn read_async(&mut self, buffer: &mut [u8]) -> Result<usize, UartError> {
+        let result = self.hc12.read_async(buffer).await;
+        self.note_read(&result);
+
+        result
+    }
+
+    /// Checks the module if [`SupervisedHc12::check_due`], otherwise returns the last status.
+    pub async fn supervise(&mut self) -> Result<SupervisionStatus, Hc12Error> {
+        if !self.check_due() {
+            return Ok(self.status);
+        }
+
+        self.check().await
+    }
+
+    /// Checks the module right away and applies the configuration again if it doesn't answer or
+    /// runs with other settings. Fails with the error of `bring_up` if that doesn't help, leaving
+    /// [`SupervisionStatus::Unreachable`].
+    pub async fn check(&mut self) -> Result<SupervisionStatus, Hc12Error> {
+        let result = self.probe().await;
+        self.finish_check(result)
+    }
+
+    async fn probe(&mut self) -> Result<SupervisionStatus, Hc12Error> {
+        if self.hc12.test().await.is_ok() {
+            if let Ok(params) = self.hc12.get_params().await {
+                if self.matches_config(&params) {
+                    return Ok(SupervisionStatus::Healthy);
+                }
+            }
+        }
+
+        self.hc12.bring_up(&self.config).await?;
+        Ok(SupervisionStatus::Restored)
+    }
+}